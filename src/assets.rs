@@ -1,6 +1,12 @@
 use bevy::{app::Plugin, asset::Handle, ecs::system::Resource, image::Image, text::Font};
 use bevy_asset_loader::asset_collection::AssetCollection;
-use toml_loader::{TomlAsset, TomlAssetPlugin};
+use toml_loader::TomlAssetPlugin;
+
+use crate::game::{
+    level_config::LevelConfig,
+    orders::order_config::{CupConfig, OrderList},
+    taps::tap_config::TapList,
+};
 
 pub mod toml_loader;
 
@@ -15,9 +21,13 @@ impl Plugin for AssetInitializerPlugin {
 #[derive(AssetCollection, Resource)]
 pub struct OrderAssets {
     #[asset(path = "orders.toml")]
-    pub order_types: Handle<TomlAsset>,
+    pub order_types: Handle<OrderList>,
     #[asset(path = "cup_config.toml")]
-    pub cup_config: Handle<TomlAsset>,
+    pub cup_config: Handle<CupConfig>,
+    #[asset(path = "taps.toml")]
+    pub tap_config: Handle<TapList>,
+    #[asset(path = "level.toml")]
+    pub level_config: Handle<LevelConfig>,
     #[asset(path = "fonts/ARCADECLASSIC.TTF")]
     pub order_font: Handle<Font>,
     #[asset(path = "sprites/cup-small.png")]
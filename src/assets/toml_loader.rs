@@ -1,24 +1,53 @@
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
+
 use bevy::{
     app::Plugin,
-    asset::{Asset, AssetApp, AssetLoader},
-    reflect::TypePath,
+    asset::{io::file::FileAssetReader, Asset, AssetApp, AssetLoader},
 };
+use serde::de::DeserializeOwned;
 use thiserror::Error;
 
+use crate::game::{
+    level_config::LevelConfig,
+    orders::order_config::{CupConfig, OrderList},
+    taps::tap_config::TapList,
+};
+
 pub struct TomlAssetPlugin;
 
 impl Plugin for TomlAssetPlugin {
     fn build(&self, app: &mut bevy::app::App) {
-        app.register_asset_loader(TomlLoader)
-            .init_asset::<TomlAsset>();
+        app.init_asset::<OrderList>()
+            .init_asset::<CupConfig>()
+            .init_asset::<TapList>()
+            .init_asset::<LevelConfig>()
+            .register_asset_loader(TypedTomlLoader::<OrderList>::default())
+            .register_asset_loader(TypedTomlLoader::<CupConfig>::default())
+            .register_asset_loader(TypedTomlLoader::<TapList>::default())
+            .register_asset_loader(TypedTomlLoader::<LevelConfig>::default());
     }
 }
 
-#[derive(Default)]
-pub struct TomlLoader;
+/// checks a parsed config makes sense, returning every problem found instead of stopping at the first
+pub trait ValidateConfig {
+    fn validate(&self) -> Vec<String> {
+        Vec::new()
+    }
+}
+
+/// deserializes a toml file straight into `T`, the handle's type picks which loader runs
+pub struct TypedTomlLoader<T>(PhantomData<fn() -> T>);
 
-#[derive(Debug, TypePath, Asset)]
-pub struct TomlAsset(pub String);
+impl<T> Default for TypedTomlLoader<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
 
 #[non_exhaustive]
 #[derive(Debug, Error)]
@@ -27,12 +56,281 @@ pub enum TomlLoaderError {
     Io(#[from] std::io::Error),
     #[error("file is not utf-8")]
     Utf8(#[from] std::str::Utf8Error),
-    #[error("toml format incorrect")]
-    Toml,
+    #[error("{0}")]
+    Toml(TomlError),
+    #[error("{} has {} problem(s):\n{}", .path.display(), .problems.len(), .problems.join("\n"))]
+    Invalid { path: PathBuf, problems: Vec<String> },
+}
+
+/// a toml error pointing at where in the file it happened
+#[derive(Debug)]
+pub struct TomlError {
+    pub path: PathBuf,
+    pub line: Option<usize>,
+    pub column: Option<usize>,
+    /// dotted key the error is under, array of tables entries are indexed from 0
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl TomlError {
+    fn new(path: PathBuf, source: &str, error: toml::de::Error) -> Self {
+        let (line, column, key) = match error.span() {
+            Some(span) => {
+                let before = &source[..span.start];
+                let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+                (
+                    Some(before.matches('\n').count() + 1),
+                    Some(span.start - line_start + 1),
+                    key_at(source, span.start),
+                )
+            }
+            None => (None, None, None),
+        };
+
+        Self {
+            path,
+            line,
+            column,
+            key,
+            message: error.message().trim().to_owned(),
+        }
+    }
+}
+
+impl Display for TomlError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.path.display())?;
+        if let (Some(line), Some(column)) = (self.line, self.column) {
+            write!(f, " line {line}, column {column}")?;
+        }
+        if let Some(key) = &self.key {
+            write!(f, " in `{key}`")?;
+        }
+        write!(f, ": {}", self.message)
+    }
+}
+
+enum KeyPart {
+    Key(String),
+    Index(usize),
+}
+
+enum Container {
+    Array,
+    InlineTable,
+}
+
+/// works out the dotted key of the value at `offset`, following tables, inline tables and arrays
+/// that are split over several lines
+fn key_at(source: &str, offset: usize) -> Option<String> {
+    let mut table: Vec<KeyPart> = Vec::new();
+    let mut array_lengths: HashMap<String, usize> = HashMap::new();
+    //keys and array indexes from the table down to the value being read
+    let mut path: Vec<KeyPart> = Vec::new();
+    let mut containers: Vec<Container> = Vec::new();
+    let mut expecting_key = true;
+    let mut key = String::new();
+
+    let mut index = 0;
+    while index < offset {
+        let rest = &source[index..];
+        let Some(char) = rest.chars().next() else {
+            break;
+        };
+
+        if containers.is_empty() && expecting_key && key.trim().is_empty() && char == '[' {
+            let line = rest.split('\n').next().unwrap_or(rest);
+            if let Some((name, is_array)) = read_header(line) {
+                table = vec![KeyPart::Key(name.to_owned())];
+                if is_array {
+                    let length = array_lengths.entry(name.to_owned()).or_default();
+                    table.push(KeyPart::Index(*length));
+                    *length += 1;
+                }
+                path.clear();
+            }
+            index += line.len();
+            continue;
+        }
+
+        match char {
+            '#' => {
+                index += rest.find('\n').unwrap_or(rest.len());
+                continue;
+            }
+            '"' | '\'' => {
+                let length = string_length(rest);
+                if expecting_key {
+                    key.push_str(&rest[..length]);
+                }
+                index += length;
+                continue;
+            }
+            '\n' if containers.is_empty() => {
+                if !expecting_key {
+                    path.pop();
+                    expecting_key = true;
+                }
+                key.clear();
+            }
+            '=' if expecting_key => {
+                path.push(KeyPart::Key(key.trim().trim_matches(['"', '\'']).to_owned()));
+                key.clear();
+                expecting_key = false;
+            }
+            _ if expecting_key => {
+                if char == '}' {
+                    containers.pop();
+                    expecting_key = false;
+                } else if char != ',' {
+                    key.push(char);
+                }
+            }
+            '[' => {
+                containers.push(Container::Array);
+                path.push(KeyPart::Index(0));
+            }
+            '{' => {
+                containers.push(Container::InlineTable);
+                expecting_key = true;
+            }
+            ']' => {
+                containers.pop();
+                path.pop();
+            }
+            '}' => {
+                containers.pop();
+                path.pop();
+            }
+            ',' => match containers.last() {
+                Some(Container::Array) => {
+                    if let Some(KeyPart::Index(element)) = path.last_mut() {
+                        *element += 1;
+                    }
+                }
+                Some(Container::InlineTable) => {
+                    path.pop();
+                    expecting_key = true;
+                }
+                None => {}
+            },
+            _ => {}
+        }
+        index += char.len_utf8();
+    }
+
+    //errors about a whole table or a key point at the header or key instead of a value
+    if expecting_key && key.trim().is_empty() {
+        let line = source[offset..].split('\n').next().unwrap_or_default();
+        match read_header(line) {
+            Some((name, is_array)) if containers.is_empty() => {
+                table = vec![KeyPart::Key(name.to_owned())];
+                if is_array {
+                    table.push(KeyPart::Index(array_lengths.get(name).copied().unwrap_or(0)));
+                }
+                path.clear();
+            }
+            _ => {
+                if let Some((name, _)) = line.split_once('=') {
+                    path.push(KeyPart::Key(name.trim().trim_matches(['"', '\'']).to_owned()));
+                }
+            }
+        }
+    }
+
+    let mut key = String::new();
+    for part in table.iter().chain(path.iter()) {
+        match part {
+            KeyPart::Key(name) if key.is_empty() => key.push_str(name),
+            KeyPart::Key(name) => {
+                key.push('.');
+                key.push_str(name);
+            }
+            KeyPart::Index(element) => key.push_str(&format!("[{element}]")),
+        }
+    }
+    (!key.is_empty()).then_some(key)
+}
+
+/// the name in a `[table]` or `[[array of tables]]` header at the start of `line`
+fn read_header(line: &str) -> Option<(&str, bool)> {
+    if let Some((name, _)) = line.strip_prefix("[[").and_then(|rest| rest.split_once("]]")) {
+        return Some((name.trim(), true));
+    }
+    line.strip_prefix('[')
+        .and_then(|rest| rest.split_once(']'))
+        .map(|(name, _)| (name.trim(), false))
+}
+
+/// bytes taken up by the string starting `source`, quotes included
+fn string_length(source: &str) -> usize {
+    for quotes in ["\"\"\"", "'''"] {
+        if let Some(rest) = source.strip_prefix(quotes) {
+            return rest.find(quotes).map_or(source.len(), |end| end + 2 * quotes.len());
+        }
+    }
+
+    let quote = if source.starts_with('"') { '"' } else { '\'' };
+    let mut escaped = false;
+    for (index, char) in source.char_indices().skip(1) {
+        match char {
+            '\\' if quote == '"' && !escaped => escaped = true,
+            '\n' => return index,
+            _ if char == quote && !escaped => return index + 1,
+            _ => escaped = false,
+        }
+    }
+    source.len()
+}
+
+fn parse<T: DeserializeOwned + ValidateConfig>(path: &Path, toml_str: &str) -> Result<T, TomlLoaderError> {
+    let config: T = toml::from_str(toml_str)
+        .map_err(|error| TomlLoaderError::Toml(TomlError::new(path.to_owned(), toml_str, error)))?;
+
+    let problems = config.validate();
+    if !problems.is_empty() {
+        return Err(TomlLoaderError::Invalid {
+            path: path.to_owned(),
+            problems,
+        });
+    }
+
+    Ok(config)
 }
 
-impl AssetLoader for TomlLoader {
-    type Asset = TomlAsset;
+/// parses and validates every config file without starting the game, returns false if any are broken
+pub fn check_config() -> bool {
+    let assets = FileAssetReader::get_base_path().join("assets");
+    [
+        check_file::<OrderList>(&assets, "orders.toml"),
+        check_file::<CupConfig>(&assets, "cup_config.toml"),
+        check_file::<TapList>(&assets, "taps.toml"),
+        check_file::<LevelConfig>(&assets, "level.toml"),
+    ]
+    .into_iter()
+    .all(|valid| valid)
+}
+
+fn check_file<T: DeserializeOwned + ValidateConfig>(assets: &Path, path: &str) -> bool {
+    let result = std::fs::read_to_string(assets.join(path))
+        .map_err(TomlLoaderError::from)
+        .and_then(|toml_str| parse::<T>(Path::new(path), &toml_str));
+
+    match result {
+        Ok(_) => {
+            println!("{path} ok");
+            true
+        }
+        Err(error) => {
+            eprintln!("{error}");
+            false
+        }
+    }
+}
+
+impl<T: Asset + DeserializeOwned + ValidateConfig> AssetLoader for TypedTomlLoader<T> {
+    type Asset = T;
 
     type Settings = ();
 
@@ -42,17 +340,120 @@ impl AssetLoader for TomlLoader {
         &self,
         reader: &mut dyn bevy::asset::io::Reader,
         _settings: &Self::Settings,
-        _load_context: &mut bevy::asset::LoadContext<'_>,
+        load_context: &mut bevy::asset::LoadContext<'_>,
     ) -> Result<Self::Asset, Self::Error> {
         let mut bytes = Vec::new();
         reader.read_to_end(&mut bytes).await?;
         let toml_str = std::str::from_utf8(&bytes)?;
-        let asset = TomlAsset(toml_str.to_owned());
-
-        Ok(asset)
+        parse(load_context.path(), toml_str)
     }
 
     fn extensions(&self) -> &[&str] {
         &["toml"]
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::level_config::LevelConfig;
+
+    fn toml_error<T: DeserializeOwned + ValidateConfig>(source: &str) -> TomlError {
+        match parse::<T>(Path::new("test.toml"), source) {
+            Err(TomlLoaderError::Toml(error)) => error,
+            Err(error) => panic!("expected a toml error, got {error}"),
+            Ok(_) => panic!("expected a toml error"),
+        }
+    }
+
+    #[test]
+    fn key_inside_multi_line_sections() {
+        let error = toml_error::<OrderList>(
+            r#"
+[[orders]]
+name = "Red"
+difficulty = 0
+sections = [{ color = [255, 0.0, 0.0], size = 30 }]
+
+[[orders]]
+name = "Mixed"
+difficulty = 1
+sections = [
+    { color = [255, 0.0, 0.0], size = 10 },
+    { color = [0.0, 34.0, 255.0], size = "x" },
+]
+"#,
+        );
+        assert_eq!(error.key.as_deref(), Some("orders[1].sections[1].size"));
+        assert_eq!(error.line, Some(12));
+    }
+
+    #[test]
+    fn key_inside_inline_table() {
+        let error = toml_error::<TapList>(
+            r#"
+# pour = { mode = "drip" } is optional
+[[taps]]
+name = "TAP 1"
+color = [255.0, 0.0, 0.0]
+position = [-149.0, 12.0]
+outlet = [-280.0, 110.0]
+flow_rate = 5.0
+reservoir = 300
+refill_time = 3.0
+pour = { mode = "drip", burst_size = "two", cooldown = 0.25 }
+"#,
+        );
+        assert_eq!(error.key.as_deref(), Some("taps[0].pour.burst_size"));
+    }
+
+    #[test]
+    fn key_inside_multi_line_array_of_arrays() {
+        let source = r#"
+waste_budget = 10
+
+[order_spawning]
+# [seconds into the shift, seconds between new orders]
+spawn_interval = [
+    [0, 4],
+    [60, "3"],
+]
+"#;
+        let error = toml_error::<LevelConfig>(source);
+        assert_eq!(error.key.as_deref(), Some("order_spawning.spawn_interval[1][1]"));
+
+        let after_array = format!("{source}max_pending = \"six\"\n").replace("\"3\"", "3");
+        let error = toml_error::<LevelConfig>(&after_array);
+        assert_eq!(error.key.as_deref(), Some("order_spawning.max_pending"));
+    }
+
+    #[test]
+    fn missing_field_points_at_its_table() {
+        let error = toml_error::<OrderList>(
+            r#"
+[[orders]]
+name = "Red"
+difficulty = 0
+sections = [{ color = [255, 0.0, 0.0], size = 30 }]
+
+[[orders]]
+name = "Blue"
+sections = [{ color = [0.0, 34.0, 255.0], size = 30 }]
+"#,
+        );
+        assert_eq!(error.key.as_deref(), Some("orders[1]"));
+    }
+
+    #[test]
+    fn key_at_ignores_brackets_in_strings_and_comments() {
+        let source = "[[orders]]\nname = \"[a] = {b\" # [c] = {\nsize = 1\n";
+        assert_eq!(key_at(source, source.find('1').unwrap()).as_deref(), Some("orders[0].size"));
+    }
+
+    #[test]
+    fn error_display_has_line_and_key() {
+        let error = toml_error::<LevelConfig>("waste_budget = \"lots\"\n");
+        let message = error.to_string();
+        assert!(message.starts_with("test.toml line 1, column 16 in `waste_budget`: "), "{message}");
+    }
+}
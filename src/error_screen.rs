@@ -0,0 +1,102 @@
+use bevy::{
+    app::{Plugin, Update},
+    asset::{AssetLoadError, UntypedAssetLoadFailedEvent},
+    color::Color,
+    ecs::{
+        component::Component,
+        event::EventReader,
+        query::With,
+        schedule::{common_conditions::resource_changed, IntoSystemConfigs},
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    hierarchy::{BuildChildren, ChildBuild},
+    state::{condition::in_state, state::OnEnter},
+    text::{TextColor, TextFont},
+    ui::{widget::Text, BackgroundColor, FlexDirection, Node, UiRect, Val},
+    utils::default,
+};
+
+use crate::GameStates;
+
+/// shows why assets failed to load instead of crashing on a broken config file
+pub struct ErrorScreenPlugin;
+
+impl Plugin for ErrorScreenPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.init_resource::<LoadErrors>();
+        app.add_systems(Update, collect_load_errors);
+        app.add_systems(OnEnter(GameStates::LoadError), setup);
+        app.add_systems(
+            Update,
+            update_error_text
+                .run_if(in_state(GameStates::LoadError))
+                .run_if(resource_changed::<LoadErrors>),
+        );
+    }
+}
+
+/// every asset load failure seen so far, one line each
+#[derive(Resource, Default)]
+pub struct LoadErrors(Vec<String>);
+
+#[derive(Component)]
+struct ErrorText;
+
+fn collect_load_errors(
+    mut failures: EventReader<UntypedAssetLoadFailedEvent>,
+    mut load_errors: ResMut<LoadErrors>,
+) {
+    for failure in failures.read() {
+        let message = match &failure.error {
+            // the loader error is prefixed with the asset path and the loader's full type name
+            AssetLoadError::AssetLoaderError(error) => {
+                let error = error.to_string();
+                match error.split_once("': ") {
+                    Some((_, message)) => message.to_owned(),
+                    None => error,
+                }
+            }
+            error => format!("{}: {}", failure.path, error),
+        };
+        load_errors.0.push(message);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                width: Val::Percent(100.),
+                height: Val::Percent(100.),
+                flex_direction: FlexDirection::Column,
+                padding: UiRect::all(Val::Px(20.)),
+                row_gap: Val::Px(10.),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.15, 0.05, 0.05)),
+        ))
+        .with_children(|root| {
+            root.spawn((
+                Text::new("Could not load game assets"),
+                TextFont {
+                    font_size: 24.,
+                    ..default()
+                },
+            ));
+            root.spawn((
+                ErrorText,
+                Text::default(),
+                TextFont {
+                    font_size: 12.,
+                    ..default()
+                },
+                TextColor(Color::srgb(1., 0.8, 0.8)),
+            ));
+        });
+}
+
+fn update_error_text(mut texts: Query<&mut Text, With<ErrorText>>, load_errors: Res<LoadErrors>) {
+    for mut text in texts.iter_mut() {
+        text.0 = load_errors.0.join("\n\n");
+    }
+}
@@ -1,16 +1,20 @@
-use bevy::{app::Plugin, ecs::{component::Component, system::ResMut}, state::{app::AppExtStates, state::{NextState, OnEnter, OnExit, StateSet, SubStates}}};
+use bevy::{app::{Plugin, Update}, asset::Assets, ecs::{component::Component, event::EventReader, schedule::IntoSystemConfigs, system::{Commands, Res, ResMut}}, state::{app::AppExtStates, condition::in_state, state::{NextState, OnEnter, OnExit, State, StateSet, SubStates}}};
 use controls::ControlPlugin;
 use game_ui::GameUiPlugin;
+use level_config::LevelConfig;
 use orders::OrderPlugin;
+use spills::SpillPlugin;
 use taps::TapsPlugin;
 
-use crate::{despawn_screen, GameStates};
+use crate::{assets::OrderAssets, despawn_screen, GameStates};
 
 pub mod controls;
 pub mod orders;
 pub mod taps;
 pub mod status_bar;
 pub mod game_ui;
+pub mod level_config;
+pub mod spills;
 
 pub struct GamePlugin(GameStates);
 
@@ -20,10 +24,14 @@ impl Plugin for GamePlugin {
             TapsPlugin::run_on_state(self.0.clone()),
             OrderPlugin::run_on_state(self.0.clone()),
             ControlPlugin::run_on_state(self.0.clone()),
-            GameUiPlugin::run_on_state(self.0.clone())
+            GameUiPlugin::run_on_state(self.0.clone()),
+            SpillPlugin::run_on_state(self.0.clone()),
         ));
-        app.add_systems(OnExit(self.0.clone()), despawn_screen::<GameScreen>);
+        app.add_systems(OnEnter(self.0.clone()), setup_level_config);
+        app.add_systems(OnExit(self.0.clone()), (despawn_screen::<GameScreen>, remove_level_config));
         app.add_sub_state::<LevelState>();
+        app.add_event::<LevelFailure>();
+        app.add_systems(Update, apply_failures.run_if(in_state(self.0.clone())));
         app.add_systems(OnEnter(LevelState::GameOver), end_game);
     }
 }
@@ -43,10 +51,18 @@ pub trait StatePlugin<T: Plugin> {
 #[derive(Component)]
 pub struct GameScreen;
 
+#[derive(Debug, Clone, Copy)]
 pub enum Event{
-    FailedOrder
+    FailedOrder,
+    WasteBudgetExceeded,
+    /// a customer gave up waiting for their order to reach a tap
+    WalkOut,
 }
 
+/// sent by whatever caused a failure, apply_failures counts them all into the LevelState
+#[derive(bevy::ecs::event::Event, Debug, Clone, Copy)]
+pub struct LevelFailure(pub Event);
+
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash, SubStates)] 
 #[source(GameStates = GameStates::Playing)]
 pub enum LevelState {
@@ -62,18 +78,77 @@ impl LevelState {
         use LevelState::*;
 
         match (self, event){
-            (NoFailures, FailedOrder) => OrdersFailed(1),
-            (OrdersFailed(val), FailedOrder) => {
+            (NoFailures, FailedOrder | WasteBudgetExceeded | WalkOut) => OrdersFailed(1),
+            (OrdersFailed(val), FailedOrder | WasteBudgetExceeded | WalkOut) => {
                 if val + 1 > 3 {
                     return GameOver;
                 }
                 OrdersFailed(val + 1)
             },
-            (GameOver, FailedOrder) => GameOver,
+            (GameOver, _) => GameOver,
         }
     }
 }
 
+/// several failures can land in the same frame, setting the next state once for each would lose all but one
+fn apply_failures(
+    mut failures: EventReader<LevelFailure>,
+    state: Res<State<LevelState>>,
+    mut next_state: ResMut<NextState<LevelState>>,
+) {
+    if failures.is_empty() {
+        return;
+    }
+
+    let level_state = failures
+        .read()
+        .fold(*state.get(), |level_state, failure| level_state.next(&failure.0));
+    next_state.set(level_state);
+}
+
+fn setup_level_config(
+    mut commands: Commands,
+    order_asset: Res<OrderAssets>,
+    level_configs: Res<Assets<LevelConfig>>,
+) {
+    let level_config = level_configs
+        .get(order_asset.level_config.id())
+        .expect("level.toml is missing")
+        .clone();
+
+    commands.insert_resource(level_config);
+}
+
+fn remove_level_config(mut commands: Commands) {
+    commands.remove_resource::<LevelConfig>();
+}
+
 fn end_game(mut next_state: ResMut<NextState<GameStates>>){
     next_state.set(GameStates::EndScreen);
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{app::App, state::app::StatesPlugin, MinimalPlugins};
+
+    use super::*;
+
+    #[test]
+    fn failures_in_the_same_frame_all_count() {
+        let mut app = App::new();
+        app.add_plugins((MinimalPlugins, StatesPlugin))
+            .insert_state(GameStates::Playing)
+            .add_sub_state::<LevelState>()
+            .add_event::<LevelFailure>()
+            .add_systems(Update, apply_failures);
+        app.update();
+
+        app.world_mut().send_event(LevelFailure(Event::FailedOrder));
+        app.world_mut().send_event(LevelFailure(Event::WalkOut));
+        app.world_mut().send_event(LevelFailure(Event::WasteBudgetExceeded));
+        app.update();
+        app.update();
+
+        assert_eq!(*app.world().resource::<State<LevelState>>().get(), LevelState::OrdersFailed(3));
+    }
+}
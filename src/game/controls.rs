@@ -1,20 +1,27 @@
 use bevy::{
     app::{Plugin, Update},
     ecs::{
+        query::With,
         schedule::IntoSystemConfigs,
-        system::{Commands, Res, ResMut, Resource},
+        system::{Commands, Query, Res, ResMut, Resource},
     },
-    input::{keyboard::KeyCode, ButtonInput},
+    input::{keyboard::KeyCode, mouse::MouseButton, ButtonInput},
+    render::camera::Camera,
     state::{
         condition::in_state,
         state::{OnEnter, OnExit},
     },
+    transform::components::GlobalTransform,
+    window::{PrimaryWindow, Window},
 };
 
 use crate::GameStates;
 
 use super::{
-    taps::{tap_state::TapSpeed, DrinkInput, DrinkOutput, TapState},
+    taps::{
+        hoses::{socket_at, DraggedHose, InputSocket, OutputSocket},
+        OutputId, TapState,
+    },
     StatePlugin,
 };
 
@@ -24,7 +31,10 @@ impl Plugin for ControlPlugin {
     fn build(&self, app: &mut bevy::app::App) {
         app.add_systems(OnEnter(self.0.clone()), add_resources);
         app.add_systems(OnExit(self.0.clone()), remove_resources);
-        app.add_systems(Update, control_system.run_if(in_state(self.0.clone())));
+        app.add_systems(
+            Update,
+            (control_system, mouse_control_system).run_if(in_state(self.0.clone())),
+        );
     }
 }
 
@@ -35,7 +45,7 @@ impl StatePlugin<ControlPlugin> for ControlPlugin {
 }
 
 fn add_resources(mut commands: Commands) {
-    commands.insert_resource(SelectedTap(Option::None));
+    commands.insert_resource(SelectedTap(Option::None, Option::None));
 }
 
 fn remove_resources(mut commands: Commands) {
@@ -43,7 +53,29 @@ fn remove_resources(mut commands: Commands) {
 }
 
 #[derive(Resource)]
-pub struct SelectedTap(Option<DrinkOutput>);
+/// output picked for the next key press, and the output whose pour button is being held
+pub struct SelectedTap(Option<OutputId>, Option<OutputId>);
+
+/// keys used to pick outputs and inputs, the nth key selects the nth registered id
+const SELECT_KEYS: [KeyCode; 9] = [
+    KeyCode::Numpad1,
+    KeyCode::Numpad2,
+    KeyCode::Numpad3,
+    KeyCode::Numpad4,
+    KeyCode::Numpad5,
+    KeyCode::Numpad6,
+    KeyCode::Numpad7,
+    KeyCode::Numpad8,
+    KeyCode::Numpad9,
+];
+
+/// drops per second added or removed per key press
+const FLOW_RATE_STEP: f32 = 0.5;
+const LIGHTNESS_STEP: f32 = 0.1;
+
+fn just_pressed_index(keys: &ButtonInput<KeyCode>) -> Option<usize> {
+    SELECT_KEYS.iter().position(|key| keys.just_pressed(*key))
+}
 
 /// temporary keyboard controls for development
 fn control_system(
@@ -52,63 +84,113 @@ fn control_system(
     mut selected_tap: ResMut<SelectedTap>,
 ) {
     if selected_tap.0 == Option::None {
-        if keys.just_pressed(KeyCode::Numpad1) {
-            selected_tap.0 = Option::Some(DrinkOutput::Color1);
-        }
-        if keys.just_pressed(KeyCode::Numpad2) {
-            selected_tap.0 = Option::Some(DrinkOutput::Color2);
-        }
-        if keys.just_pressed(KeyCode::Numpad3) {
-            selected_tap.0 = Option::Some(DrinkOutput::Color3);
-        }
-        if keys.just_pressed(KeyCode::Numpad4) {
-            selected_tap.0 = Option::Some(DrinkOutput::Mixer1);
-        }
-        if keys.just_pressed(KeyCode::Numpad5) {
-            selected_tap.0 = Option::Some(DrinkOutput::Mixer2);
+        if let Some(index) = just_pressed_index(&keys) {
+            selected_tap.0 = tap_state.output_ids().get(index).copied();
         }
     } else {
-        let output = selected_tap.0.as_ref().unwrap();
-        if keys.just_pressed(KeyCode::Numpad1) {
-            tap_state.make_connection(output.clone(), DrinkInput::Tap1);
-            selected_tap.0 = Option::None;
-        } else if keys.just_pressed(KeyCode::Numpad2) {
-            tap_state.make_connection(output.clone(), DrinkInput::Tap2);
-            selected_tap.0 = Option::None;
-        } else if keys.just_pressed(KeyCode::Numpad3) {
-            tap_state.make_connection(output.clone(), DrinkInput::Tap3);
-            selected_tap.0 = Option::None;
-        } else if keys.just_pressed(KeyCode::Numpad4) {
-            tap_state.make_connection(output.clone(), DrinkInput::Mixer1);
-            selected_tap.0 = Option::None;
-        } else if keys.just_pressed(KeyCode::Numpad5) {
-            tap_state.make_connection(output.clone(), DrinkInput::Mixer2);
+        let output = selected_tap.0.unwrap();
+        if let Some(index) = just_pressed_index(&keys) {
+            if let Some(input) = tap_state.input_ids().get(index) {
+                tap_state.make_connection(output, *input);
+            }
             selected_tap.0 = Option::None;
         } else if keys.just_pressed(KeyCode::ArrowUp) {
-            tap_state.output_switch(true, output.clone());
+            tap_state.output_switch(true, output);
             selected_tap.0 = Option::None;
         } else if keys.just_pressed(KeyCode::ArrowDown) {
-            tap_state.output_switch(false, output.clone());
+            tap_state.output_switch(false, output);
             selected_tap.0 = Option::None;
         } else if keys.just_pressed(KeyCode::ArrowRight) {
-            tap_state.mixer_switch(true, output.clone());
+            tap_state.mixer_switch(true, output);
             selected_tap.0 = Option::None;
         } else if keys.just_pressed(KeyCode::ArrowLeft) {
-            tap_state.mixer_switch(false, output.clone());
+            tap_state.mixer_switch(false, output);
             selected_tap.0 = Option::None;
         } else if keys.just_pressed(KeyCode::Space) {
-            tap_state.drop_pressed(output.clone());
+            tap_state.pour_pressed(output);
+            selected_tap.1 = Option::Some(output);
+            selected_tap.0 = Option::None;
+        } else if keys.just_pressed(KeyCode::KeyR) {
+            tap_state.start_refill(output);
+            selected_tap.0 = Option::None;
+        } else if keys.just_pressed(KeyCode::Delete) {
+            tap_state.disconnect(output);
             selected_tap.0 = Option::None;
+        } else if keys.just_pressed(KeyCode::NumpadAdd) {
+            //keep the output selected so the rate can be tuned with repeated presses
+            tap_state.adjust_flow_rate(output, FLOW_RATE_STEP);
+        } else if keys.just_pressed(KeyCode::NumpadSubtract) {
+            tap_state.adjust_flow_rate(output, -FLOW_RATE_STEP);
+        } else if keys.just_pressed(KeyCode::NumpadMultiply) {
+            //dilution dial, turns the selected tap's color towards white
+            tap_state.adjust_lightness(output, LIGHTNESS_STEP);
+        } else if keys.just_pressed(KeyCode::NumpadDivide) {
+            tap_state.adjust_lightness(output, -LIGHTNESS_STEP);
         }
     }
 
-    if keys.just_pressed(KeyCode::Digit1) {
-        tap_state.speed = TapSpeed::Slow
+    if keys.just_released(KeyCode::Space) {
+        if let Some(output) = selected_tap.1.take() {
+            tap_state.pour_released(output);
+        }
     }
-    else if keys.just_pressed(KeyCode::Digit2) {
-        tap_state.speed = TapSpeed::Medium
+
+    //panic button, pulls every hose out
+    if keys.just_pressed(KeyCode::Backspace) {
+        tap_state.disconnect_all();
+        selected_tap.0 = Option::None;
     }
-    else if keys.just_pressed(KeyCode::Digit3) {
-        tap_state.speed = TapSpeed::Fast
+}
+/// drag a hose from an outlet and release it over an inlet to connect them,
+/// right click a socket to pull its hose out
+fn mouse_control_system(
+    mouse: Res<ButtonInput<MouseButton>>,
+    windows: Query<&Window, With<PrimaryWindow>>,
+    cameras: Query<(&Camera, &GlobalTransform)>,
+    output_sockets: Query<(&OutputSocket, &GlobalTransform)>,
+    input_sockets: Query<(&InputSocket, &GlobalTransform)>,
+    mut tap_state: ResMut<TapState>,
+    mut dragged_hose: ResMut<DraggedHose>,
+) {
+    let cursor = match (windows.get_single(), cameras.get_single()) {
+        (Ok(window), Ok((camera, camera_transform))) => window
+            .cursor_position()
+            .and_then(|position| camera.viewport_to_world_2d(camera_transform, position).ok()),
+        _ => None,
+    };
+
+    let Some(cursor) = cursor else {
+        if mouse.just_released(MouseButton::Left) {
+            dragged_hose.output = None;
+        }
+        return;
+    };
+
+    if mouse.just_pressed(MouseButton::Left) {
+        dragged_hose.output = socket_at(output_sockets.iter(), cursor).map(|socket| socket.0);
     }
-}
\ No newline at end of file
+    dragged_hose.end = cursor;
+
+    if mouse.just_released(MouseButton::Left) {
+        if let Some(output) = dragged_hose.output.take() {
+            if let Some(input_socket) = socket_at(input_sockets.iter(), cursor) {
+                tap_state.make_connection(output, input_socket.0);
+            }
+        }
+    }
+
+    if mouse.just_pressed(MouseButton::Right) {
+        if let Some(output_socket) = socket_at(output_sockets.iter(), cursor) {
+            tap_state.disconnect(output_socket.0);
+        } else if let Some(input_socket) = socket_at(input_sockets.iter(), cursor) {
+            let connected_output = tap_state
+                .connections()
+                .into_iter()
+                .find(|(_, input)| *input == input_socket.0)
+                .map(|(output, _)| output);
+            if let Some(output) = connected_output {
+                tap_state.disconnect(output);
+            }
+        }
+    }
+}
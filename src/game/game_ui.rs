@@ -1,15 +1,15 @@
-use bevy::{app::{Plugin, Update}, ecs::{component::Component, entity::Entity, query::With, schedule::IntoSystemConfigs, system::{Commands, Query, Res}}, hierarchy::{BuildChildren, ChildBuild}, state::{condition::in_state, state::{OnEnter, State}}, transform::components::GlobalTransform, ui::{widget::ImageNode, JustifyContent, Node, Val}, utils::default};
+use bevy::{app::{Plugin, Update}, color::Color, ecs::{component::Component, entity::Entity, query::With, schedule::IntoSystemConfigs, system::{Commands, Query, Res}}, hierarchy::{BuildChildren, ChildBuild}, state::{condition::in_state, state::{OnEnter, State}}, transform::components::GlobalTransform, ui::{widget::ImageNode, BackgroundColor, JustifyContent, Node, Val}, utils::default};
 
 use crate::{assets::GameUiAssets, GameStates};
 
-use super::{GameScreen, LevelState, StatePlugin};
+use super::{level_config::LevelConfig, spills::SpillTracker, GameScreen, LevelState, StatePlugin};
 
 pub struct GameUiPlugin(GameStates);
 
 impl Plugin for GameUiPlugin {
     fn build(&self, app: &mut bevy::app::App) {
         app.add_systems(OnEnter(self.0.clone()), setup);
-        app.add_systems(Update, (update_chalkboard).run_if(in_state(self.0.clone())));
+        app.add_systems(Update, (update_chalkboard, update_waste_meter).run_if(in_state(self.0.clone())));
     }
 }
 
@@ -51,6 +51,30 @@ fn setup(mut commands: Commands, assets: Res<GameUiAssets>){
                 chalkboard.spawn(checkbox.clone());
             }
         });
+
+        //waste meter, fills up as drops are spilled
+        root.spawn((
+            Node {
+                position_type: bevy::ui::PositionType::Absolute,
+                top: Val::Px(125.),
+                right: Val::Px(50.),
+                width: Val::Px(134.),
+                height: Val::Px(10.),
+                ..default()
+            },
+            BackgroundColor(Color::srgb(0.2, 0.2, 0.2)),
+        ))
+        .with_children(|meter| {
+            meter.spawn((
+                WasteMeterFill,
+                Node {
+                    width: Val::Percent(0.),
+                    height: Val::Percent(100.),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.55, 0.35, 0.15)),
+            ));
+        });
     });
 
 }
@@ -75,4 +99,14 @@ fn update_chalkboard(mut commands: Commands, image_nodes: Query<(Entity, &Global
         image_node.remove::<CheckBoxEmpty>();
         image_node.insert((CheckBoxFailed, ImageNode::new(assets.checkbox_failed.clone())));
     });
-}
\ No newline at end of file
+}
+
+#[derive(Component, Debug, Clone)]
+struct WasteMeterFill;
+
+fn update_waste_meter(mut fills: Query<&mut Node, With<WasteMeterFill>>, spill_tracker: Res<SpillTracker>, level_config: Res<LevelConfig>){
+    let percent = spill_tracker.meter_percent(level_config.waste_budget) * 100.;
+    for mut fill in fills.iter_mut() {
+        fill.width = Val::Percent(percent);
+    }
+}
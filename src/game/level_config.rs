@@ -0,0 +1,79 @@
+use bevy::{asset::Asset, ecs::system::Resource, reflect::TypePath};
+use serde::Deserialize;
+
+use crate::assets::toml_loader::ValidateConfig;
+
+#[derive(Deserialize, Resource, Asset, TypePath, Clone)]
+pub struct LevelConfig {
+    pub waste_budget: usize,
+    #[serde(default)]
+    pub order_spawning: SpawnCurve,
+}
+
+/// how orders come in over the course of a shift
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SpawnCurve {
+    /// [seconds into the shift, seconds between orders] points, interpolated between and held past the last one
+    pub spawn_interval: Vec<[f32; 2]>,
+    /// orders waiting for a tap before no more are spawned
+    pub max_pending: usize,
+    /// seconds between another order type being unlocked
+    pub unlock_interval: f32,
+    /// seconds a pending order waits for a tap before the customer walks out
+    pub patience: f32,
+}
+
+impl Default for SpawnCurve {
+    fn default() -> Self {
+        Self {
+            spawn_interval: vec![[0., 3.]],
+            max_pending: 6,
+            unlock_interval: 5.,
+            patience: 30.,
+        }
+    }
+}
+
+impl SpawnCurve {
+    pub fn spawn_interval_at(&self, shift_time: f32) -> f32 {
+        let points = &self.spawn_interval;
+        match points.iter().position(|[time, _]| *time > shift_time) {
+            Some(0) => points[0][1],
+            Some(index) => {
+                let [start_time, start_interval] = points[index - 1];
+                let [end_time, end_interval] = points[index];
+                let progress = (shift_time - start_time) / (end_time - start_time);
+                start_interval + (end_interval - start_interval) * progress
+            }
+            None => points.last().map_or(3., |[_, interval]| *interval),
+        }
+    }
+}
+
+impl ValidateConfig for LevelConfig {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        let spawning = &self.order_spawning;
+        if spawning.spawn_interval.is_empty() {
+            problems.push("order_spawning.spawn_interval needs at least one point".to_owned());
+        }
+        if spawning.spawn_interval.iter().any(|[_, interval]| *interval <= 0.) {
+            problems.push("order_spawning.spawn_interval intervals must be above 0".to_owned());
+        }
+        if spawning.spawn_interval.windows(2).any(|points| points[1][0] <= points[0][0]) {
+            problems.push("order_spawning.spawn_interval points must be in increasing time order".to_owned());
+        }
+        if spawning.max_pending == 0 {
+            problems.push("order_spawning.max_pending must be at least 1".to_owned());
+        }
+        if spawning.unlock_interval <= 0. {
+            problems.push("order_spawning.unlock_interval must be above 0".to_owned());
+        }
+        if spawning.patience <= 0. {
+            problems.push("order_spawning.patience must be above 0".to_owned());
+        }
+
+        problems
+    }
+}
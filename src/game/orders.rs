@@ -1,11 +1,11 @@
 use std::{any::Any, char::MAX, time::Duration};
 
 use bevy::{
-    app::{Plugin, Update}, asset::{Assets, Handle}, color::Color, ecs::{
+    app::{Plugin, Update}, asset::{AssetEvent, Assets, Handle}, color::Color, ecs::{
         component::Component,
         entity::Entity,
-        event::EventReader,
-        query::With,
+        event::{EventReader, EventWriter},
+        query::{With, Without},
         schedule::IntoSystemConfigs,
         system::{Commands, Query, Res, ResMut, Resource},
     }, hierarchy::{BuildChildren, ChildBuild, Children, DespawnRecursiveExt, Parent}, math::primitives::Rectangle, render::{
@@ -13,7 +13,7 @@ use bevy::{
         view::Visibility,
     }, sprite::{ColorMaterial, Material2d, MeshMaterial2d, Sprite}, state::{
         condition::in_state,
-        state::{NextState, OnEnter, OnExit, State},
+        state::{OnEnter, OnExit},
     }, text::{Text2d, TextFont, TextLayout}, time::{Time, Timer}, transform::components::Transform, utils::{
         default,
         hashbrown::{
@@ -26,33 +26,46 @@ use bevy_rapier2d::{
     prelude::{ActiveEvents, Collider, CollisionEvent, Sensor},
     rapier::prelude::CollisionEventFlags,
 };
-use order_config::{CupConfig, OrderConfig, OrderList, SectionConfig};
+use grading::{OrderGraded, OrderResult};
+use order_config::{CupConfig, OrderConfig, OrderList, SectionConfig, SizeMultipliers};
 use rand::{distr::{Distribution, StandardUniform}, seq::{IndexedRandom, IteratorRandom}, Rng};
+use ticket_rail::{add_tickets, remove_tickets, setup_ticket_rail, update_ticket_patience};
 
 use crate::{
-    assets::{toml_loader::TomlAsset, OrderAssets},
-    GameStates,
+    assets::OrderAssets,
+    GameStates, Score,
 };
 
 use super::{
-    status_bar::StatusBarMaterial, taps::{ColorDrop, Tap}, Event::FailedOrder, GameScreen, LevelState, StatePlugin
+    status_bar::StatusBarMaterial, taps::{color_mixing::dilute, drop_pool::{release_drop, DropMaterials, PooledDrop}, ColorDrop, Tap}, level_config::LevelConfig, Event::{FailedOrder, WalkOut}, GameScreen, LevelFailure, StatePlugin
 };
 
-mod order_config;
+pub mod grading;
+pub mod order_config;
+mod ticket_rail;
 
 pub struct OrderPlugin(GameStates);
 
 impl Plugin for OrderPlugin {
     fn build(&self, app: &mut bevy::app::App) {
-        app.add_systems(OnEnter(self.0.clone()), (setup_orders, setup_cup_meshes));
+        app.add_event::<OrderGraded>();
+        app.add_systems(OnEnter(self.0.clone()), (setup_orders, setup_cup_meshes, setup_ticket_rail));
         app.add_systems(
             Update,
             (
                 spawn_orders,
+                //the sync point between them despawns walked out orders before they can be assigned
+                expire_pending_orders.before(assign_pending_orders),
                 assign_pending_orders,
                 add_drops_to_cups,
+                serve_full_cups,
                 add_next_order_type,
                 update_order_timers,
+                reload_orders,
+                reload_cup_config,
+                add_tickets,
+                remove_tickets,
+                update_ticket_patience,
             )
                 .run_if(in_state(self.0.clone())),
         );
@@ -69,7 +82,8 @@ impl StatePlugin<OrderPlugin> for OrderPlugin {
 fn remove_resources(mut commands: Commands) {
     commands.remove_resource::<AvailableOrders>();
     commands.remove_resource::<CupMaterials>();
-    commands.remove_resource::<OrderSpawnTimer>();
+    commands.remove_resource::<CupMeshes>();
+    commands.remove_resource::<OrderSpawner>();
     commands.remove_resource::<OrdersWithDifficulty>();
     commands.remove_resource::<CupConfig>();
 }
@@ -77,6 +91,10 @@ fn remove_resources(mut commands: Commands) {
 #[derive(Component, Debug, Clone)]
 pub struct PendingOrder(Order);
 
+/// how much longer a pending order will wait for a tap
+#[derive(Component, Debug)]
+pub struct Patience(Timer);
+
 #[derive(Component, Debug, Clone)]
 pub struct Order{
     order_type: OrderType,
@@ -89,6 +107,20 @@ pub struct Order{
 pub struct OrderType {
     sections: Vec<Section>, //treat 0 as buttom of the cup
     name: String,
+    time_limit: f32,
+    size_multipliers: SizeMultipliers,
+    color_tolerance: f32,
+}
+
+impl OrderType {
+    fn time_limit(&self, size: &OrderSize) -> Duration {
+        let multiplier = match size {
+            OrderSize::Small => self.size_multipliers.small,
+            OrderSize::Medium => self.size_multipliers.medium,
+            OrderSize::Large => self.size_multipliers.large,
+        };
+        Duration::from_secs_f32(self.time_limit * multiplier)
+    }
 }
 
 impl From<&OrderConfig> for OrderType {
@@ -98,6 +130,9 @@ impl From<&OrderConfig> for OrderType {
         Self {
             sections,
             name: value.name.clone(),
+            time_limit: value.time_limit(),
+            size_multipliers: value.size_multipliers.clone(),
+            color_tolerance: value.color_tolerance,
         }
 
     }
@@ -118,7 +153,7 @@ impl From<&SectionConfig> for Section {
             );
 
             Self {
-                color,
+                color: dilute(color, value.lightness),
                 size: value.size
             }
     }
@@ -153,8 +188,26 @@ pub struct AvailableOrders(Vec<OrderType>);
 #[derive(Resource)]
 pub struct OrdersWithDifficulty(HashMap<u32, Vec<OrderType>>);
 
-#[derive(Component)]
-struct OrderDifficutlyAdder(Timer);
+impl From<&OrderList> for OrdersWithDifficulty {
+    fn from(order_list: &OrderList) -> Self {
+        let mut orders: HashMap<u32, Vec<OrderType>> = HashMap::new();
+        order_list.orders.iter().for_each(|order_config| {
+            let order_type = OrderType::from(order_config);
+
+            match orders.entry(order_config.difficulty) {
+                Occupied(o) => {
+                    let orders = o.into_mut();
+                    orders.push(order_type);
+                }
+                Vacant(v) => {
+                    v.insert(vec![order_type]);
+                }
+            };
+        });
+
+        OrdersWithDifficulty(orders)
+    }
+}
 
 impl OrdersWithDifficulty {
     fn get_starter_orders(&mut self) -> Vec<OrderType> {
@@ -175,71 +228,92 @@ impl OrdersWithDifficulty {
 
         order_option
     }
+
+    /// takes out every order whose name is in `names`
+    fn take_named(&mut self, names: &[String]) -> Vec<OrderType> {
+        let mut taken = Vec::new();
+        for orders in self.0.values_mut() {
+            let (named, rest): (Vec<OrderType>, Vec<OrderType>) =
+                orders.drain(..).partition(|order| names.contains(&order.name));
+            taken.extend(named);
+            *orders = rest;
+        }
+        self.0.retain(|_, value| value.len() > 0);
+
+        taken
+    }
 }
 
 pub fn setup_orders(
     mut commands: Commands,
     order_asset: Res<OrderAssets>,
-    toml_assets: Res<Assets<TomlAsset>>,
+    order_lists: Res<Assets<OrderList>>,
 ) {
-    let toml_str = toml_assets
+    let order_list = order_lists
         .get(order_asset.order_types.id())
-        .expect("orders.toml is missing")
-        .0
-        .as_str();
-    let order_list: OrderList = toml::from_str(toml_str).expect("orders.toml format is incorrect");
-
-    let mut orders: HashMap<u32, Vec<OrderType>> = HashMap::new();
-    order_list.orders.iter().for_each(|order_config| {
-        let order_type = OrderType::from(order_config);
-
-        match orders.entry(order_config.difficulty) {
-            Occupied(o) => {
-                let orders = o.into_mut();
-                orders.push(order_type);
-            }
-            Vacant(v) => {
-                v.insert(vec![order_type]);
-            }
-        };
-    });
+        .expect("orders.toml is missing");
 
-    let mut orders = OrdersWithDifficulty(orders);
+    let mut orders = OrdersWithDifficulty::from(order_list);
 
     commands.insert_resource(AvailableOrders(orders.get_starter_orders()));
     commands.insert_resource(orders);
-    commands.insert_resource(OrderSpawnTimer(Timer::new(
-        Duration::from_secs(3),
-        bevy::time::TimerMode::Repeating,
-    )));
-    commands.spawn((
-        OrderDifficutlyAdder(Timer::new(
-            Duration::from_secs(5),
-            bevy::time::TimerMode::Repeating,
-        )),
-        GameScreen,
-    ));
+    commands.insert_resource(OrderSpawner::default());
 }
 
-#[derive(Resource)]
-pub struct OrderSpawnTimer(Timer);
+/// how far into the shift we are, paced by the level's spawn curve
+#[derive(Resource, Default)]
+pub struct OrderSpawner {
+    shift_time: Duration,
+    since_spawn: Duration,
+    since_unlock: Duration,
+}
 
 fn spawn_orders(
     mut commands: Commands,
     available_orders: Res<AvailableOrders>,
-    mut order_timer: ResMut<OrderSpawnTimer>,
+    mut spawner: ResMut<OrderSpawner>,
+    pending_orders: Query<&PendingOrder>,
+    level_config: Res<LevelConfig>,
     time: Res<Time>,
 ) {
-    order_timer.0.tick(time.delta());
-    if order_timer.0.just_finished() {
+    spawner.shift_time += time.delta();
+    spawner.since_spawn += time.delta();
+
+    let spawning = &level_config.order_spawning;
+    let interval = spawning.spawn_interval_at(spawner.shift_time.as_secs_f32());
+    if spawner.since_spawn.as_secs_f32() >= interval && pending_orders.iter().len() < spawning.max_pending {
+        spawner.since_spawn = Duration::ZERO;
         let order_to_spawn = available_orders.0.choose(&mut rand::rng());
         if let Some(order_type) = order_to_spawn {
-            commands.spawn(PendingOrder(Order {
-                order_type: order_type.clone(),
-                recieved: Vec::new(),
-                time_remaining: Timer::new(Duration::from_secs(60), bevy::time::TimerMode::Once),
-                size: rand::rng().random()
-            }));
+            let size = rand::rng().random();
+            commands.spawn((
+                PendingOrder(Order {
+                    order_type: order_type.clone(),
+                    recieved: Vec::new(),
+                    time_remaining: Timer::new(order_type.time_limit(&size), bevy::time::TimerMode::Once),
+                    size,
+                }),
+                Patience(Timer::new(
+                    Duration::from_secs_f32(spawning.patience),
+                    bevy::time::TimerMode::Once,
+                )),
+                GameScreen,
+            ));
+        }
+    }
+}
+
+fn expire_pending_orders(
+    mut commands: Commands,
+    mut pending_orders: Query<(Entity, &mut Patience), With<PendingOrder>>,
+    time: Res<Time>,
+    mut failures: EventWriter<LevelFailure>,
+) {
+    for (entity, mut patience) in pending_orders.iter_mut() {
+        patience.0.tick(time.delta());
+        if patience.0.finished() {
+            commands.entity(entity).despawn();
+            failures.send(LevelFailure(WalkOut));
         }
     }
 }
@@ -247,22 +321,83 @@ fn spawn_orders(
 fn add_next_order_type(
     mut available_order: ResMut<AvailableOrders>,
     mut orders_with_difficutly: ResMut<OrdersWithDifficulty>,
-    mut query: Query<&mut OrderDifficutlyAdder>,
+    mut spawner: ResMut<OrderSpawner>,
+    level_config: Res<LevelConfig>,
     time: Res<Time>,
 ) {
-    if let Ok(mut timer) = query.get_single_mut() {
-        timer.0.tick(time.delta());
-        if timer.0.just_finished() {
-            if let Some(order) = orders_with_difficutly.get_lowest_difficulty_order() {
-                available_order.0.push(order);
-            }
+    spawner.since_unlock += time.delta();
+
+    let unlock_interval = Duration::from_secs_f32(level_config.order_spawning.unlock_interval);
+    if spawner.since_unlock >= unlock_interval {
+        spawner.since_unlock -= unlock_interval;
+        if let Some(order) = orders_with_difficutly.get_lowest_difficulty_order() {
+            available_order.0.push(order);
         }
     }
 }
 
+/// picks up edits to orders.toml while playing.
+/// orders that were already unlocked stay unlocked, cups in flight keep the order they were given
+fn reload_orders(
+    mut asset_events: EventReader<AssetEvent<OrderList>>,
+    order_asset: Res<OrderAssets>,
+    order_lists: Res<Assets<OrderList>>,
+    mut available_orders: ResMut<AvailableOrders>,
+    mut orders_with_difficulty: ResMut<OrdersWithDifficulty>,
+) {
+    if !asset_events
+        .read()
+        .any(|event| event.is_modified(order_asset.order_types.id()))
+    {
+        return;
+    }
+    let Some(order_list) = order_lists.get(order_asset.order_types.id()) else {
+        return;
+    };
+
+    let unlocked: Vec<String> = available_orders.0.iter().map(|order| order.name.clone()).collect();
+    let mut orders = OrdersWithDifficulty::from(order_list);
+    let mut available = orders.get_starter_orders();
+    available.extend(orders.take_named(&unlocked));
+
+    available_orders.0 = available;
+    *orders_with_difficulty = orders;
+}
+
+/// picks up edits to cup_config.toml while playing, only cups spawned afterwards use the new sizes
+fn reload_cup_config(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<CupConfig>>,
+    order_asset: Res<OrderAssets>,
+    cup_configs: Res<Assets<CupConfig>>,
+    mut cup_materials: ResMut<CupMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    if !asset_events
+        .read()
+        .any(|event| event.is_modified(order_asset.cup_config.id()))
+    {
+        return;
+    }
+    let Some(cup_config) = cup_configs.get(order_asset.cup_config.id()) else {
+        return;
+    };
+
+    cup_materials.divider_material = materials.add(Color::linear_rgb(cup_config.divider_color[0], cup_config.divider_color[1], cup_config.divider_color[2]));
+    commands.insert_resource(cup_config.clone());
+}
+
 #[derive(Component)]
 pub struct Cup;
 
+/// the cup config a cup was spawned with, so reloading the config doesn't change cups already out
+#[derive(Component, Debug, Clone)]
+struct CupDimensions {
+    inner_width: f32,
+    height: f32,
+    bottom_thickness: f32,
+}
+
 #[derive(Component)]
 pub struct CupDivider;
 
@@ -274,22 +409,34 @@ struct CupMaterials {
     divider_material: Handle<ColorMaterial>,
 }
 
+/// rectangle meshes shared between cups, keyed by their size
+#[derive(Resource, Default)]
+struct CupMeshes(HashMap<[u32; 2], Handle<Mesh>>);
+
+impl CupMeshes {
+    fn rectangle(&mut self, width: f32, height: f32, meshes: &mut Assets<Mesh>) -> Handle<Mesh> {
+        self.0
+            .entry([width.to_bits(), height.to_bits()])
+            .or_insert_with(|| meshes.add(Rectangle::new(width, height)))
+            .clone()
+    }
+}
+
 pub fn setup_cup_meshes(
     mut commands: Commands,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    toml_assets: Res<Assets<TomlAsset>>,
+    cup_configs: Res<Assets<CupConfig>>,
     order_asset: Res<OrderAssets>,
 ) {
-    let toml_str = toml_assets
+    let cup_config = cup_configs
         .get(order_asset.cup_config.id())
-        .expect("orders.toml is missing")
-        .0
-        .as_str();
-    let cup_config: CupConfig = toml::from_str(toml_str).expect("orders.toml format is incorrect");
+        .expect("cup_config.toml is missing")
+        .clone();
 
     commands.insert_resource(CupMaterials {
         divider_material: materials.add(Color::linear_rgb(cup_config.divider_color[0], cup_config.divider_color[1], cup_config.divider_color[2])),
     });
+    commands.insert_resource(CupMeshes::default());
     commands.insert_resource(cup_config);
 }
 
@@ -312,6 +459,7 @@ fn assign_pending_orders(
     pending_orders: Query<(Entity, &PendingOrder)>,
     mut taps: Query<(Entity, &mut OpenForOrder), With<Tap>>,
     divider_material: Res<CupMaterials>,
+    mut cup_meshes: ResMut<CupMeshes>,
     mut meshes: ResMut<Assets<Mesh>>,
     time: Res<Time>,
     order_assets: Res<OrderAssets>,
@@ -348,6 +496,15 @@ fn assign_pending_orders(
                             OrderSize::Large => order_assets.cup_large.clone(),
                         }),
                         pending_order.0.clone(),
+                        CupDimensions {
+                            inner_width: match &pending_order.0.size {
+                                OrderSize::Small => cup_config.cup_small_inner_width,
+                                OrderSize::Medium => cup_config.cup_medium_inner_width,
+                                OrderSize::Large => cup_config.cup_large_inner_width,
+                            },
+                            height: cup_config.cup_height,
+                            bottom_thickness: cup_config.cup_bottom_thickness,
+                        },
                         Transform::from_xyz(0., -12. + (- 151. / 2.) + cup_config.cup_height / 2., 40.),
                         Visibility::Visible,
                     ))
@@ -377,7 +534,7 @@ fn assign_pending_orders(
                         for divider_pos in dividers.iter() {
                             cup.spawn((
                                 CupDivider,
-                                Mesh2d(meshes.add(Rectangle::new(cup_inner_width, 2.))),
+                                Mesh2d(cup_meshes.rectangle(cup_inner_width, 2., &mut meshes)),
                                 MeshMaterial2d(divider_material.divider_material.clone()),
                                 Transform::from_xyz(
                                     0.0,
@@ -420,7 +577,7 @@ fn assign_pending_orders(
                         //order status bar 
                         cup.spawn((
                             CupStatusBar(status_bar_material.clone()),
-                            Mesh2d(meshes.add(Rectangle::new(cup_config.status_bar_width, 20.))),
+                            Mesh2d(cup_meshes.rectangle(cup_config.status_bar_width, 20., &mut meshes)),
                             MeshMaterial2d(status_bar_material),
                             Transform::from_xyz(1., -cup_config.cup_height / 2. - 50., 0.)
                         ));
@@ -435,17 +592,35 @@ struct CupStatusBar(Handle<StatusBarMaterial>);
 #[derive(Component)]
 struct CupFill;
 
+/// seconds a full cup waits before being taken away, anything poured in meanwhile is overfill
+const SERVE_DELAY: f32 = 0.5;
+
+/// cup that has been filled and is about to be taken away
+#[derive(Component)]
+struct Serving {
+    timer: Timer,
+    overfill: usize,
+}
+
+impl Serving {
+    fn new() -> Serving {
+        Serving {
+            timer: Timer::new(Duration::from_secs_f32(SERVE_DELAY), bevy::time::TimerMode::Once),
+            overfill: 0,
+        }
+    }
+}
+
 fn add_drops_to_cups(
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
-    mut active_orders: Query<(Entity, &mut Order, &Parent)>,
+    mut active_orders: Query<(Entity, &mut Order, &CupDimensions, Option<&mut Serving>)>,
     mut colliders: Query<(&mut Transform, &Parent), With<CupFillCollider>>,
-    drops: Query<(&ColorDrop, Entity)>,
+    drops: Query<(&ColorDrop, Entity), Without<PooledDrop>>,
+    mut cup_meshes: ResMut<CupMeshes>,
     mut meshes: ResMut<Assets<Mesh>>,
+    mut drop_materials: ResMut<DropMaterials>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    cup_config: Res<CupConfig>,
-    state: Res<State<LevelState>>,
-    mut next_state: ResMut<NextState<LevelState>>,
 ) {
     for event in collision_events.read() {
         match event {
@@ -467,7 +642,7 @@ fn add_drops_to_cups(
                     Err(_) => continue,
                 };
 
-                let (order_entity, mut order, tap_id) =
+                let (order_entity, mut order, cup, serving) =
                     match active_orders.get_mut(collider_parent.get()) {
                         Ok(res) => res,
                         Err(_) => continue,
@@ -475,96 +650,40 @@ fn add_drops_to_cups(
                 let total_sections: usize = order.order_type.sections.iter().map(|section| section.size).sum::<usize>() * get_order_size(&order.size);
 
                 if order.recieved.len() >= total_sections {
-                    if let Some(entity) = commands.get_entity(order_entity) {
-                        entity.despawn_recursive();
-                        commands.entity(drop_entity).despawn();
-                        commands
-                            .entity(tap_id.get())
-                            .insert(OpenForOrder(Timer::new(
-                                Duration::from_secs(2),
-                                bevy::time::TimerMode::Once,
-                            )));
-                            if is_cup_failed(&order.order_type.sections, &order.recieved, &order.size) {
-                                next_state.set(state.get().next(&FailedOrder));
-                            }
+                    if let Some(mut serving) = serving {
+                        serving.overfill += 1;
                     }
+                    release_drop(&mut commands, drop_entity);
                     continue;
                 }
                 order.recieved.push(color.0.clone());
 
-                let cup_inner_width = match &order.size {
-                    OrderSize::Small => cup_config.cup_small_inner_width,
-                    OrderSize::Medium => cup_config.cup_medium_inner_width,
-                    OrderSize::Large => cup_config.cup_large_inner_width,
-                };
-
-                let section_height = (cup_config.cup_height - cup_config.cup_bottom_thickness)/ total_sections as f32;
+                let section_height = (cup.height - cup.bottom_thickness)/ total_sections as f32;
                 commands.entity(order_entity).with_child((
                     CupFill,
-                    Mesh2d(meshes.add(Rectangle::new(
-                        cup_inner_width,
-                        section_height,
-                    ))),
-                    MeshMaterial2d(materials.add(color.0.clone())),
+                    Mesh2d(cup_meshes.rectangle(cup.inner_width, section_height, &mut meshes)),
+                    MeshMaterial2d(drop_materials.get_or_add(color.0, &mut materials)),
                     Transform::from_xyz(
                         0.0,
-                        ((cup_config.cup_height  - cup_config.cup_bottom_thickness)/ -2.)
+                        ((cup.height  - cup.bottom_thickness)/ -2.)
                             + section_height * order.recieved.len() as f32
-                            + cup_config.cup_bottom_thickness,
+                            + cup.bottom_thickness,
                         -1.0,
                     ),
                 ));
 
-                commands.entity(drop_entity).despawn();
+                release_drop(&mut commands, drop_entity);
 
                 transform.translation.y += section_height;
+                if order.recieved.len() == total_sections {
+                    commands.entity(order_entity).insert(Serving::new());
+                }
             }
             _ => {}
         }
     }
 }
 
-const COLOR_RANGE: f32 = 20.;
-const MAX_FAILURES_PER_SECTION: usize = 4;
-
-fn is_cup_failed(expected: &Vec<Section>, recieved: &Vec<Color>, size: &OrderSize) -> bool {
-    let mut index = 0;
-    for section in expected.iter() {
-        let section_size = section.size * get_order_size(size);
-
-        if index + section_size > recieved.len() {
-            return true;
-        };
-        let slice = &recieved[index..section_size + index];
-        let equal_colors = slice
-            .iter()
-            .filter(|recieved_color| color_equal(&section.color, *recieved_color, COLOR_RANGE))
-            .count();
-        if section_size - equal_colors > MAX_FAILURES_PER_SECTION {
-            return true;
-        }
-        index += section_size;
-    }
-
-    false
-}
-
-fn color_equal(color1: &Color, color2: &Color, range: f32) -> bool {
-    let color1 = color1.to_linear();
-    let color2 = color2.to_linear();
-
-    let red = color1.red;
-    let blue = color2.blue;
-    let green = color2.green;
-
-    color2.red >= red - range
-        && color2.red <= red + range
-        && color2.blue >= blue - range
-        && color2.blue <= blue + range
-        && color2.green >= green - range
-        && color2.green <= green + range
-}
-
 fn get_order_size(size: &OrderSize) -> usize {
         match size {
             OrderSize::Small => 1,
@@ -573,13 +692,40 @@ fn get_order_size(size: &OrderSize) -> usize {
         }
 }
 
+/// takes full cups away once they've sat for a moment and grades them
+fn serve_full_cups(
+    mut commands: Commands,
+    mut cups: Query<(Entity, &Order, &mut Serving, &Parent)>,
+    time: Res<Time>,
+    mut graded: EventWriter<OrderGraded>,
+    mut score: ResMut<Score>,
+    mut failures: EventWriter<LevelFailure>,
+) {
+    for (entity, order, mut serving, tap) in cups.iter_mut() {
+        serving.timer.tick(time.delta());
+        if !serving.timer.finished() {
+            continue;
+        }
+
+        let result = OrderResult::grade(order, serving.overfill);
+        if result.failed() {
+            failures.send(LevelFailure(FailedOrder));
+        }
+        score.0 += result.stars() as usize;
+        graded.send(OrderGraded(result));
+
+        commands.entity(entity).despawn_recursive();
+        commands.entity(tap.get()).insert(OpenForOrder::new());
+    }
+}
+
 fn update_order_timers(
-    mut orders: Query<(&mut Order, Entity, &Parent)>,
+    mut orders: Query<(&mut Order, Entity, &Parent), Without<Serving>>,
     status_bars: Query<(&CupStatusBar, &Parent)>,
     mut status_bar_materials: ResMut<Assets<StatusBarMaterial>>,
     time: Res<Time>,
-    state: Res<State<LevelState>>,
-    mut next_state: ResMut<NextState<LevelState>>,
+    mut graded: EventWriter<OrderGraded>,
+    mut failures: EventWriter<LevelFailure>,
     mut commands: Commands
 ){
     orders.iter_mut().for_each(|(mut order, _, _)| {
@@ -592,8 +738,10 @@ fn update_order_timers(
                 Some(material) => {
                     let timer = &order.time_remaining;
                     if timer.finished() {
-                        //send failure event
-                        next_state.set(state.get().next(&FailedOrder));
+                        //an unfinished cup is always underfilled, so always a failure
+                        let result = OrderResult::grade(order, 0);
+                        failures.send(LevelFailure(FailedOrder));
+                        graded.send(OrderGraded(result));
                         commands.entity(entity).despawn_recursive();
                         commands.entity(tap.get()).insert(OpenForOrder::new());
                         continue;
@@ -0,0 +1,211 @@
+use std::time::Duration;
+
+use bevy::{color::Color, ecs::event::Event};
+
+use crate::game::taps::color_mixing::perceptual_distance;
+
+use super::{get_order_size, Order, Section};
+
+/// a section with less of its drops the right color than this fails the whole order
+const MIN_SECTION_ACCURACY: f32 = 0.85;
+/// drops either side of a layer boundary checked for a clean edge, scaled by cup size
+const BOUNDARY_WINDOW: usize = 2;
+const THREE_STAR_SCORE: f32 = 0.9;
+const TWO_STAR_SCORE: f32 = 0.75;
+
+/// how well a drink was made, worked out once its cup is served or runs out of time
+#[derive(Debug, Clone)]
+pub struct OrderResult {
+    pub name: String,
+    /// fraction of each section's drops that were the right color, from the bottom of the cup up
+    pub section_accuracy: Vec<f32>,
+    /// fraction of the drops around each layer boundary on the right side of it, 1 with no boundaries
+    pub boundary_sharpness: f32,
+    /// drops the cup holds
+    pub capacity: usize,
+    /// drops poured after the cup was full
+    pub overfill: usize,
+    /// drops still missing when the cup was taken away
+    pub underfill: usize,
+    pub time_taken: Duration,
+    pub time_limit: Duration,
+}
+
+impl OrderResult {
+    pub fn grade(order: &Order, overfill: usize) -> Self {
+        let multiplier = get_order_size(&order.size);
+        let tolerance = order.order_type.color_tolerance;
+        let sections = &order.order_type.sections;
+        let total: usize = sections.iter().map(|section| section.size * multiplier).sum();
+
+        let mut section_accuracy = Vec::new();
+        let mut boundaries = Vec::new();
+        let mut start = 0;
+        for (index, section) in sections.iter().enumerate() {
+            let size = section.size * multiplier;
+            let matching = order.recieved.iter().skip(start).take(size)
+                .filter(|color| matches(section, color, tolerance))
+                .count();
+            section_accuracy.push(matching as f32 / size as f32);
+
+            start += size;
+            if let Some(next) = sections.get(index + 1) {
+                boundaries.push(boundary_sharpness(
+                    &order.recieved,
+                    start,
+                    BOUNDARY_WINDOW * multiplier,
+                    (section, next),
+                    tolerance,
+                ));
+            }
+        }
+
+        let boundary_sharpness = match boundaries.len() {
+            0 => 1.,
+            count => boundaries.iter().sum::<f32>() / count as f32,
+        };
+
+        Self {
+            name: order.order_type.name.clone(),
+            section_accuracy,
+            boundary_sharpness,
+            capacity: total,
+            overfill,
+            underfill: total.saturating_sub(order.recieved.len()),
+            time_taken: order.time_remaining.elapsed(),
+            time_limit: order.time_remaining.duration(),
+        }
+    }
+
+    pub fn failed(&self) -> bool {
+        self.underfill > 0
+            || self.section_accuracy.iter().any(|accuracy| *accuracy < MIN_SECTION_ACCURACY)
+    }
+
+    /// 0 to 1, color accuracy counts for most of it with clean layers and speed making up the rest.
+    /// overfilling takes off the fraction of the cup that was wasted
+    pub fn score(&self) -> f32 {
+        let accuracy = match self.section_accuracy.len() {
+            0 => 0.,
+            count => self.section_accuracy.iter().sum::<f32>() / count as f32,
+        };
+        let speed = 1. - self.time_taken.div_duration_f32(self.time_limit).clamp(0., 1.);
+        let wasted = self.overfill as f32 / self.capacity.max(1) as f32;
+
+        (accuracy * 0.6 + self.boundary_sharpness * 0.2 + speed * 0.2 - wasted).clamp(0., 1.)
+    }
+
+    /// 0 stars is a failed order, otherwise 1 to 3
+    pub fn stars(&self) -> u8 {
+        if self.failed() {
+            return 0;
+        }
+
+        match self.score() {
+            score if score >= THREE_STAR_SCORE => 3,
+            score if score >= TWO_STAR_SCORE => 2,
+            _ => 1,
+        }
+    }
+}
+
+/// sent when a cup is taken away, served or not
+#[derive(Event, Debug, Clone)]
+pub struct OrderGraded(pub OrderResult);
+
+fn matches(section: &Section, color: &Color, tolerance: f32) -> bool {
+    perceptual_distance(section.color, *color) <= tolerance
+}
+
+fn boundary_sharpness(
+    recieved: &[Color],
+    boundary: usize,
+    window: usize,
+    (below, above): (&Section, &Section),
+    tolerance: f32,
+) -> f32 {
+    let start = boundary.saturating_sub(window);
+    let below_matching = recieved.iter().take(boundary).skip(start)
+        .filter(|color| matches(below, color, tolerance))
+        .count();
+    let above_matching = recieved.iter().skip(boundary).take(window)
+        .filter(|color| matches(above, color, tolerance))
+        .count();
+
+    (below_matching + above_matching) as f32 / (boundary - start + window) as f32
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::time::{Timer, TimerMode};
+
+    use super::*;
+    use crate::game::orders::{order_config::SizeMultipliers, OrderSize, OrderType};
+
+    const RED: Color = Color::linear_rgb(255., 0., 0.);
+    const BLUE: Color = Color::linear_rgb(0., 34., 255.);
+    const YELLOW: Color = Color::linear_rgb(234., 255., 0.);
+
+    /// a small red under blue order with `section_size` drops of each
+    fn order(section_size: usize, recieved: Vec<Color>) -> Order {
+        Order {
+            order_type: OrderType {
+                sections: vec![
+                    Section { color: RED, size: section_size },
+                    Section { color: BLUE, size: section_size },
+                ],
+                name: "test".to_owned(),
+                time_limit: 60.,
+                size_multipliers: SizeMultipliers::default(),
+                color_tolerance: 0.05,
+            },
+            recieved,
+            time_remaining: Timer::from_seconds(60., TimerMode::Once),
+            size: OrderSize::Small,
+        }
+    }
+
+    fn layers(layers: &[(Color, usize)]) -> Vec<Color> {
+        layers.iter().flat_map(|(color, count)| std::iter::repeat_n(*color, *count)).collect()
+    }
+
+    #[test]
+    fn perfect_cup_gets_three_stars() {
+        let result = OrderResult::grade(&order(4, layers(&[(RED, 4), (BLUE, 4)])), 0);
+        assert!(!result.failed());
+        assert_eq!(result.boundary_sharpness, 1.);
+        assert_eq!(result.stars(), 3);
+    }
+
+    #[test]
+    fn underfilled_cup_fails() {
+        let result = OrderResult::grade(&order(4, layers(&[(RED, 4), (BLUE, 3)])), 0);
+        assert_eq!(result.underfill, 1);
+        assert!(result.failed());
+        assert_eq!(result.stars(), 0);
+    }
+
+    #[test]
+    fn one_wrong_section_fails() {
+        let result = OrderResult::grade(&order(4, layers(&[(RED, 4), (BLUE, 2), (YELLOW, 2)])), 0);
+        assert_eq!(result.section_accuracy, vec![1., 0.5]);
+        assert!(result.failed());
+    }
+
+    #[test]
+    fn overfilling_lowers_the_score() {
+        let perfect = OrderResult::grade(&order(4, layers(&[(RED, 4), (BLUE, 4)])), 0);
+        let overfilled = OrderResult::grade(&order(4, layers(&[(RED, 4), (BLUE, 4)])), 4);
+        assert!(!overfilled.failed());
+        assert!(overfilled.score() < perfect.score());
+        assert!(overfilled.stars() < perfect.stars());
+    }
+
+    #[test]
+    fn smeared_boundary_lowers_sharpness() {
+        let smeared = layers(&[(RED, 7), (BLUE, 1), (RED, 1), (BLUE, 7)]);
+        let result = OrderResult::grade(&order(8, smeared), 0);
+        assert!(!result.failed());
+        assert!(result.boundary_sharpness < 1.);
+    }
+}
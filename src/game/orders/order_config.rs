@@ -1,26 +1,118 @@
-use bevy::ecs::system::Resource;
+use bevy::{asset::Asset, ecs::system::Resource, reflect::TypePath};
 use serde::Deserialize;
 
+use crate::assets::toml_loader::ValidateConfig;
 
-#[derive(Deserialize)]
+#[derive(Deserialize, Asset, TypePath)]
 pub struct OrderList {
     pub orders: Vec<OrderConfig>,
 }
 
+impl ValidateConfig for OrderList {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if !self.orders.iter().any(|order| order.difficulty == 0) {
+            problems.push("no orders have difficulty 0, a shift needs at least one to start with".to_owned());
+        }
+
+        for (index, order) in self.orders.iter().enumerate() {
+            let order_key = format!("orders[{index}] ({})", order.name);
+            if order.sections.is_empty() {
+                problems.push(format!("{order_key}: sections is empty"));
+            }
+            if order.time_limit.is_some_and(|time_limit| time_limit <= 0.) {
+                problems.push(format!("{order_key}: time_limit must be above 0"));
+            }
+            if order.color_tolerance <= 0. {
+                problems.push(format!("{order_key}: color_tolerance must be above 0"));
+            }
+            let multipliers = &order.size_multipliers;
+            if [multipliers.small, multipliers.medium, multipliers.large].iter().any(|multiplier| *multiplier <= 0.) {
+                problems.push(format!("{order_key}: size_multipliers must all be above 0"));
+            }
+
+            for (section_index, section) in order.sections.iter().enumerate() {
+                let section_key = format!("{order_key}: sections[{section_index}]");
+                if section.size == 0 {
+                    problems.push(format!("{section_key}.size must be at least 1"));
+                }
+                if section.color.iter().any(|component| !(0. ..=255.).contains(component)) {
+                    problems.push(format!(
+                        "{section_key}.color {:?} has components outside 0 to 255",
+                        section.color
+                    ));
+                }
+                if !(0. ..=1.).contains(&section.lightness) {
+                    problems.push(format!(
+                        "{section_key}.lightness {} is outside 0 to 1",
+                        section.lightness
+                    ));
+                }
+            }
+        }
+
+        problems
+    }
+}
+
 #[derive(Deserialize)]
 pub struct OrderConfig {
     pub name: String,
     pub sections: Vec<SectionConfig>,
     pub difficulty: u32,
+    /// seconds to finish a medium cup, defaults to more time the more sections there are
+    #[serde(default)]
+    pub time_limit: Option<f32>,
+    #[serde(default)]
+    pub size_multipliers: SizeMultipliers,
+    /// how far off a drop's color can look and still count, as an oklab distance
+    #[serde(default = "default_color_tolerance")]
+    pub color_tolerance: f32,
+}
+
+pub(crate) fn default_color_tolerance() -> f32 {
+    0.05
+}
+
+const BASE_TIME_LIMIT: f32 = 45.;
+const TIME_PER_SECTION: f32 = 15.;
+
+impl OrderConfig {
+    pub fn time_limit(&self) -> f32 {
+        self.time_limit
+            .unwrap_or(BASE_TIME_LIMIT + TIME_PER_SECTION * self.sections.len() as f32)
+    }
+}
+
+/// scales an order's time limit by the size of cup it's poured into
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct SizeMultipliers {
+    pub small: f32,
+    pub medium: f32,
+    pub large: f32,
+}
+
+impl Default for SizeMultipliers {
+    fn default() -> Self {
+        Self {
+            small: 1.,
+            medium: 1.,
+            large: 1.5,
+        }
+    }
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct SectionConfig{
     pub color: [f32; 3],
     pub size: usize,
+    /// how far the color is diluted towards white, 0 is the pure color
+    #[serde(default)]
+    pub lightness: f32,
 }
 
-#[derive(Deserialize, Resource)]
+#[derive(Deserialize, Resource, Asset, TypePath, Clone)]
 pub struct CupConfig {
     pub cup_small_width: f32,
     pub cup_small_inner_width: f32,
@@ -34,3 +126,5 @@ pub struct CupConfig {
     pub divider_color: [f32; 3],
     pub status_bar_width: f32
 }
+
+impl ValidateConfig for CupConfig {}
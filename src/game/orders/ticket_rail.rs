@@ -0,0 +1,160 @@
+use bevy::{
+    color::Color,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::{Added, With},
+        removal_detection::RemovedComponents,
+        system::{Commands, Query, Res},
+    },
+    hierarchy::{BuildChildren, ChildBuild, DespawnRecursiveExt},
+    text::{TextColor, TextFont},
+    ui::{widget::Text, AlignItems, BackgroundColor, FlexDirection, Node, PositionType, UiRect, Val},
+    utils::default,
+};
+
+use crate::{
+    assets::OrderAssets,
+    game::{taps::color_mixing::display_color, GameScreen},
+};
+
+use super::{OrderSize, Patience, PendingOrder};
+
+/// row along the top of the screen that tickets for queued orders hang from
+#[derive(Component)]
+pub struct TicketRail;
+
+/// ticket for the pending order entity it holds
+#[derive(Component)]
+pub struct Ticket(Entity);
+
+/// bar along the bottom of a ticket that shrinks as the customer loses patience
+#[derive(Component)]
+pub struct TicketPatience(Entity);
+
+pub fn setup_ticket_rail(mut commands: Commands) {
+    commands.spawn((
+        TicketRail,
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(4.),
+            left: Val::Px(10.),
+            right: Val::Px(200.),
+            height: Val::Px(40.),
+            flex_direction: FlexDirection::Row,
+            column_gap: Val::Px(6.),
+            ..default()
+        },
+        GameScreen,
+    ));
+}
+
+pub fn add_tickets(
+    mut commands: Commands,
+    new_orders: Query<(Entity, &PendingOrder), Added<PendingOrder>>,
+    rails: Query<Entity, With<TicketRail>>,
+    order_assets: Res<OrderAssets>,
+) {
+    let Ok(rail) = rails.get_single() else {
+        return;
+    };
+
+    for (order_entity, pending_order) in new_orders.iter() {
+        let order = &pending_order.0;
+        commands.entity(rail).with_children(|rail| {
+            rail.spawn((
+                Ticket(order_entity),
+                Node {
+                    width: Val::Px(72.),
+                    height: Val::Percent(100.),
+                    flex_direction: FlexDirection::Column,
+                    padding: UiRect::all(Val::Px(3.)),
+                    row_gap: Val::Px(3.),
+                    ..default()
+                },
+                BackgroundColor(Color::srgb(0.95, 0.92, 0.8)),
+            ))
+            .with_children(|ticket| {
+                ticket.spawn((
+                    Text::new(order.order_type.name.clone()),
+                    TextFont {
+                        font: order_assets.order_font.clone(),
+                        font_size: 10.,
+                        ..default()
+                    },
+                    TextColor(Color::BLACK),
+                ));
+
+                //cup size followed by the colors from the bottom of the cup up
+                ticket
+                    .spawn(Node {
+                        flex_direction: FlexDirection::Row,
+                        align_items: AlignItems::Center,
+                        column_gap: Val::Px(2.),
+                        ..default()
+                    })
+                    .with_children(|row| {
+                        row.spawn((
+                            Text::new(match order.size {
+                                OrderSize::Small => "S",
+                                OrderSize::Medium => "M",
+                                OrderSize::Large => "L",
+                            }),
+                            TextFont {
+                                font: order_assets.order_font.clone(),
+                                font_size: 10.,
+                                ..default()
+                            },
+                            TextColor(Color::BLACK),
+                        ));
+                        for section in order.order_type.sections.iter() {
+                            row.spawn((
+                                Node {
+                                    width: Val::Px(8.),
+                                    height: Val::Px(8.),
+                                    ..default()
+                                },
+                                BackgroundColor(display_color(section.color)),
+                            ));
+                        }
+                    });
+
+                ticket.spawn((
+                    TicketPatience(order_entity),
+                    Node {
+                        width: Val::Percent(100.),
+                        height: Val::Px(3.),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgb(0.8, 0.3, 0.2)),
+                ));
+            });
+        });
+    }
+}
+
+pub fn update_ticket_patience(
+    mut bars: Query<(&mut Node, &TicketPatience)>,
+    pending_orders: Query<&Patience, With<PendingOrder>>,
+) {
+    for (mut node, bar) in bars.iter_mut() {
+        if let Ok(patience) = pending_orders.get(bar.0) {
+            node.width = Val::Percent(patience.0.fraction_remaining() * 100.);
+        }
+    }
+}
+
+/// takes a ticket down once its order is no longer pending
+pub fn remove_tickets(
+    mut commands: Commands,
+    mut removed_orders: RemovedComponents<PendingOrder>,
+    tickets: Query<(Entity, &Ticket)>,
+) {
+    for order_entity in removed_orders.read() {
+        for (ticket_entity, ticket) in tickets.iter() {
+            if ticket.0 == order_entity {
+                commands.entity(ticket_entity).despawn_recursive();
+            }
+        }
+    }
+}
@@ -0,0 +1,103 @@
+use std::collections::HashMap;
+
+use bevy::{
+    app::{Plugin, Update},
+    color::Color,
+    ecs::{
+        event::{EventReader, EventWriter},
+        schedule::IntoSystemConfigs,
+        system::{Commands, Res, ResMut, Resource},
+    },
+    state::{
+        condition::in_state,
+        state::{OnEnter, OnExit},
+    },
+};
+
+use crate::GameStates;
+
+use super::{
+    level_config::LevelConfig,
+    taps::{drop_pool::color_key, DropSpilled, OutputId},
+    Event::WasteBudgetExceeded,
+    LevelFailure, StatePlugin,
+};
+
+pub struct SpillPlugin(GameStates);
+
+impl Plugin for SpillPlugin {
+    fn build(&self, app: &mut bevy::app::App) {
+        app.add_systems(OnEnter(self.0.clone()), add_resources);
+        app.add_systems(Update, track_spills.run_if(in_state(self.0.clone())));
+        app.add_systems(OnExit(self.0.clone()), remove_resources);
+    }
+}
+
+impl StatePlugin<SpillPlugin> for SpillPlugin {
+    fn run_on_state(state: GameStates) -> SpillPlugin {
+        SpillPlugin(state)
+    }
+}
+
+fn add_resources(mut commands: Commands) {
+    commands.insert_resource(SpillTracker::default());
+}
+
+fn remove_resources(mut commands: Commands) {
+    commands.remove_resource::<SpillTracker>();
+}
+
+#[derive(Resource, Debug, Default)]
+pub struct SpillTracker {
+    total: usize,
+    /// drops spilled since the waste budget was last used up
+    meter: usize,
+    by_color: HashMap<[i32; 3], (Color, usize)>,
+    by_output: HashMap<OutputId, usize>,
+}
+
+impl SpillTracker {
+    fn record(&mut self, color: Color, output: OutputId) {
+        self.total += 1;
+        self.meter += 1;
+        self.by_color.entry(color_key(color)).or_insert((color, 0)).1 += 1;
+        *self.by_output.entry(output).or_insert(0) += 1;
+    }
+
+    pub fn total(&self) -> usize {
+        self.total
+    }
+
+    /// how much of the waste budget has been used, from 0 to 1
+    pub fn meter_percent(&self, waste_budget: usize) -> f32 {
+        if waste_budget == 0 {
+            return 0.;
+        }
+        (self.meter as f32 / waste_budget as f32).min(1.)
+    }
+
+    /// every spilled color with how many drops of it were spilled
+    pub fn spilled_colors(&self) -> impl Iterator<Item = &(Color, usize)> {
+        self.by_color.values()
+    }
+
+    pub fn spilled_from(&self, output: OutputId) -> usize {
+        self.by_output.get(&output).copied().unwrap_or(0)
+    }
+}
+
+fn track_spills(
+    mut spills: EventReader<DropSpilled>,
+    mut spill_tracker: ResMut<SpillTracker>,
+    level_config: Res<LevelConfig>,
+    mut failures: EventWriter<LevelFailure>,
+) {
+    for spill in spills.read() {
+        spill_tracker.record(spill.color, spill.output);
+    }
+
+    if level_config.waste_budget > 0 && spill_tracker.meter >= level_config.waste_budget {
+        spill_tracker.meter = 0;
+        failures.send(LevelFailure(WasteBudgetExceeded));
+    }
+}
@@ -4,12 +4,12 @@ use bevy::{
     app::{Plugin, Update}, asset::Assets, color::Color, core::Name, ecs::{
         component::Component,
         entity::Entity,
-        event::EventReader,
-        query::With,
+        event::{Event, EventReader, EventWriter},
+        query::{With, Without},
         schedule::IntoSystemConfigs,
         system::{Commands, Query, Res, ResMut, Resource},
     }, hierarchy::{BuildChildren, ChildBuild}, math::{
-        primitives::Circle,
+        primitives::{Circle, Rectangle},
         Vec2,
     }, render::{mesh::{Mesh, Mesh2d}, view::{InheritedVisibility, Visibility}}, sprite::{ColorMaterial, MeshMaterial2d, Sprite}, state::{
         condition::in_state,
@@ -17,25 +17,57 @@ use bevy::{
     }, time::{Time, Timer, Virtual}, transform::components::Transform
 };
 use bevy_rapier2d::prelude::{
-    ActiveEvents, Collider, CollisionEvent, GravityScale, RigidBody, Velocity,
+    ActiveEvents, Collider, CollisionEvent, RigidBody, Velocity,
 };
 use rand::Rng;
-pub use tap_state::{add_tap_state, timers, DrinkInput, DrinkOutput, TapState};
+pub use tap_state::{add_tap_state, timers, InputId, OutputId, TapState};
+use color_mixing::display_color;
+use tap_config::TapList;
+use drop_pool::{collect_released_drops, release_drop, setup_drop_pool, DropAssets, DropMaterials, DropPool, NewDrop, PooledDrop};
+use tap_events::{
+    send_tap_events, ConnectionChanged, DropDispensed, FlowRateChanged, LightnessChanged,
+    MixerToggled, OutputSwitched, RefillStarted,
+};
+use hoses::{setup_hoses, update_hose_preview, update_hoses, InputSocket, OutputSocket, SOCKET_RADIUS};
 
-use crate::{assets::BarAssets, GameStates, WINDOW_HEIGHT};
+use crate::{assets::BarAssets, GameStates, WINDOW_HEIGHT, WINDOW_WIDTH};
 
 use super::{orders::OpenForOrder, GameScreen, StatePlugin};
 
+pub mod color_mixing;
+pub mod drop_pool;
+pub mod hoses;
+pub mod tap_events;
+pub mod tap_config;
 pub mod tap_state;
 
 pub struct TapsPlugin(GameStates);
 
 impl Plugin for TapsPlugin {
     fn build(&self, app: &mut bevy::app::App) {
-        app.add_systems(OnEnter(self.0.clone()), (add_tap_state, add_taps));
+        app.add_event::<DropSpilled>()
+            .add_event::<ConnectionChanged>()
+            .add_event::<OutputSwitched>()
+            .add_event::<DropDispensed>()
+            .add_event::<MixerToggled>()
+            .add_event::<FlowRateChanged>()
+            .add_event::<LightnessChanged>()
+            .add_event::<RefillStarted>();
+        app.add_systems(OnEnter(self.0.clone()), (add_tap_state, setup_hoses, setup_drop_pool, add_taps).chain());
         app.add_systems(
             Update,
-            (timers, run_taps, remove_fallen_drops).run_if(in_state(self.0.clone())),
+            (
+                timers,
+                run_taps,
+                send_tap_events.after(timers).after(run_taps),
+                remove_fallen_drops,
+                remove_out_of_bounds_drops,
+                collect_released_drops,
+                update_hoses.after(send_tap_events),
+                update_hose_preview,
+                update_reservoir_gauges,
+                update_lightness_indicators.after(send_tap_events),
+            ).run_if(in_state(self.0.clone())),
         );
         app.add_systems(OnExit(self.0.clone()), despawn_resources);
     }
@@ -49,6 +81,12 @@ impl StatePlugin<TapsPlugin> for TapsPlugin {
 
 fn despawn_resources(mut commands: Commands) {
     commands.remove_resource::<TapState>();
+    commands.remove_resource::<TapList>();
+    commands.remove_resource::<hoses::HoseMaterial>();
+    commands.remove_resource::<hoses::DraggedHose>();
+    commands.remove_resource::<DropAssets>();
+    commands.remove_resource::<DropMaterials>();
+    commands.remove_resource::<DropPool>();
 }
 
 #[derive(Component, Debug, Clone)]
@@ -56,11 +94,27 @@ pub struct TapDispenseTimer(Timer);
 #[derive(Component)]
 pub struct ColorDrop(pub Color);
 
+/// output the drop was poured from
+#[derive(Component, Debug, Clone, Copy)]
+pub struct DropSource(pub OutputId);
+
+/// sent when a drop misses every cup and is removed from play
+#[derive(Event, Debug, Clone)]
+pub struct DropSpilled {
+    pub color: Color,
+    pub output: OutputId,
+}
+
+/// how far past the window edges a drop can go before it counts as spilled
+const KILL_ZONE_MARGIN: f32 = 20.;
+
 
 fn run_taps(
     mut commands: Commands,
     mut tap_state: ResMut<TapState>,
-    mut meshes: ResMut<Assets<Mesh>>,
+    mut drop_pool: ResMut<DropPool>,
+    mut drop_materials: ResMut<DropMaterials>,
+    drop_assets: Res<DropAssets>,
     mut materials: ResMut<Assets<ColorMaterial>>,
     mut query: Query<(&Input, &Transform, &mut TapDispenseTimer)>,
     time: Res<Time<Virtual>>,
@@ -69,35 +123,32 @@ fn run_taps(
 
     for (input, transform, mut timer) in query.iter_mut() {
         timer.0.tick(time.delta());
-
-        if let Some(output_state) = tap_state.get_output_state(input.0.clone()) {
-            if timer.0.finished() && (output_state.consume_press() || output_state.on) {
-                if let Some(color) = output_state.get_drop() {
-                    timer.0.set_duration(match tap_state.speed {
-                        tap_state::TapSpeed::Slow => Duration::from_millis(400),
-                        tap_state::TapSpeed::Medium => Duration::from_millis(200),
-                        tap_state::TapSpeed::Fast => Duration::from_millis(100),
-                    });
-                    timer.0.reset();
-                    let mut new_transform = transform.clone();
-                    new_transform.translation += -transform.forward() * 2.;
-                    commands.spawn((
-                        Mesh2d(meshes.add(Circle::new(2.))),
-                        MeshMaterial2d(materials.add(color.clone())),
-                        new_transform,
-                        ColorDrop(color.clone()),
-                        RigidBody::Dynamic,
-                        GravityScale(0.4),
-                        Velocity::linear(Vec2 {
-                            x: rng.random_range(-10.0..10.),
-                            y: 0., // y: -rng.random_range(0.0..1.),
-                        }),
-                        Collider::ball(2.),
-                        GameScreen,
-                    ));
-                };
-            }
+        if !timer.0.finished() {
+            continue;
         }
+
+        //goes through tap_state so tap and mixer drops are sent in the order they were poured
+        let Some((output, color, interval)) = tap_state.take_drop(input.0) else {
+            continue;
+        };
+        timer.0.set_duration(interval);
+        timer.0.reset();
+        let mut new_transform = transform.clone();
+        new_transform.translation += -transform.forward() * 2.;
+        drop_pool.spawn(
+            &mut commands,
+            &drop_assets,
+            NewDrop {
+                material: drop_materials.get_or_add(color, &mut materials),
+                color,
+                source: output,
+                transform: new_transform,
+                velocity: Velocity::linear(Vec2 {
+                    x: rng.random_range(-10.0..10.),
+                    y: 0., // y: -rng.random_range(0.0..1.),
+                }),
+            },
+        );
     }
 }
 
@@ -105,16 +156,28 @@ fn remove_fallen_drops(
     mut commands: Commands,
     mut collision_events: EventReader<CollisionEvent>,
     bar_table: Query<Entity, With<BarTable>>,
+    drops: Query<(&ColorDrop, &DropSource), Without<PooledDrop>>,
+    mut spills: EventWriter<DropSpilled>,
 ) {
     match bar_table.get_single() {
         Ok(bar_table) => {
             for event in collision_events.read() {
                 match event {
                     CollisionEvent::Started(entity, entity1, _collision_event_flags) => {
-                        if *entity == bar_table {
-                            commands.entity(entity1.clone()).despawn();
+                        let fallen = if *entity == bar_table {
+                            *entity1
                         } else if *entity1 == bar_table {
-                            commands.entity(entity.clone()).despawn();
+                            *entity
+                        } else {
+                            continue;
+                        };
+                        //events can arrive after the drop was released, it may already be back in the pool
+                        if let Ok((drop, source)) = drops.get(fallen) {
+                            spills.send(DropSpilled {
+                                color: drop.0,
+                                output: source.0,
+                            });
+                            release_drop(&mut commands, fallen);
                         }
                     }
                     _ => {}
@@ -126,12 +189,37 @@ fn remove_fallen_drops(
     };
 }
 
+fn remove_out_of_bounds_drops(
+    mut commands: Commands,
+    drops: Query<(Entity, &ColorDrop, &DropSource, &Transform)>,
+    mut spills: EventWriter<DropSpilled>,
+) {
+    let max_x = WINDOW_WIDTH / 2. + KILL_ZONE_MARGIN;
+    let max_y = WINDOW_HEIGHT / 2. + KILL_ZONE_MARGIN;
+
+    for (entity, drop, source, transform) in drops.iter() {
+        let position = transform.translation;
+        if position.x.abs() > max_x || position.y.abs() > max_y {
+            spills.send(DropSpilled {
+                color: drop.0,
+                output: source.0,
+            });
+            release_drop(&mut commands, entity);
+        }
+    }
+}
+
 #[derive(Component)]
 struct BarTable;
 
 fn add_taps(
     mut commands: Commands,
-    bar_assets: Res<BarAssets>
+    bar_assets: Res<BarAssets>,
+    tap_list: Res<TapList>,
+    tap_state: Res<TapState>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+    mut drop_materials: ResMut<DropMaterials>,
 ) {
     //background
     commands.spawn((
@@ -151,6 +239,12 @@ fn add_taps(
         GameScreen
     ));
 
+    let socket_mesh = meshes.add(Circle::new(SOCKET_RADIUS));
+    let socket_material = materials.add(Color::linear_rgb(0.2, 0.2, 0.2));
+    let mixer_mesh = meshes.add(Rectangle::new(1., 24.));
+    let gauge_mesh = meshes.add(Rectangle::new(1., 1.));
+    let mixer_material = materials.add(Color::linear_rgb(0.5, 0.5, 0.5));
+
     let tap_timer = TapDispenseTimer(Timer::new(Duration::from_millis(200), bevy::time::TimerMode::Once));
 
     //taps
@@ -161,36 +255,142 @@ fn add_taps(
         Visibility::Visible,
         Name::new("TAPS")
     )).with_children(|parent| {
-        parent.spawn((
-            Tap,
-            Input(DrinkInput::Tap2),
-            tap_timer.clone(),
-            Transform::from_xyz(0., 12., 4.),
-            OpenForOrder::new(),
-            Name::new("TAP 2"),
-            InheritedVisibility::VISIBLE
-        ));
-        parent.spawn((
-            Tap,
-            Input(DrinkInput::Tap1),
-            tap_timer.clone(),
-            Transform::from_xyz(-149.,12., 4.),
-            OpenForOrder::new(),
-            Name::new("TAP 1"),
-            InheritedVisibility::VISIBLE
-        ));
-        parent.spawn((
-            Tap,
-            Input(DrinkInput::Tap3),
-            tap_timer.clone(),
-            Transform::from_xyz(152., 12., 4.),
-            OpenForOrder::new(),
-            Name::new("TAP 3"),
-            InheritedVisibility::VISIBLE
-        ));
+        for (input, tap_config) in tap_state.tap_ids().into_iter().zip(tap_list.taps.iter()) {
+            parent.spawn((
+                Tap,
+                Input(input),
+                tap_timer.clone(),
+                Transform::from_xyz(tap_config.position[0], tap_config.position[1], 4.),
+                OpenForOrder::new(),
+                Name::new(tap_config.name.clone()),
+                InheritedVisibility::VISIBLE
+            ));
+            parent.spawn((
+                InputSocket(input),
+                Mesh2d(socket_mesh.clone()),
+                MeshMaterial2d(socket_material.clone()),
+                Transform::from_xyz(tap_config.position[0], 151. / 2., 6.),
+            ));
+        }
+
+        for (output, tap_config) in tap_state.color_output_ids().into_iter().zip(tap_list.taps.iter()) {
+            let tap_color = materials.add(display_color(Color::linear_rgb(tap_config.color[0], tap_config.color[1], tap_config.color[2])));
+            parent.spawn((
+                OutputSocket(output),
+                Mesh2d(socket_mesh.clone()),
+                MeshMaterial2d(tap_color.clone()),
+                Transform::from_xyz(tap_config.outlet[0], tap_config.outlet[1], 6.),
+            ));
+
+            //swatch under the outlet showing the tap's color after dilution
+            parent.spawn((
+                LightnessIndicator(output),
+                Mesh2d(gauge_mesh.clone()),
+                MeshMaterial2d(drop_materials.get_or_add(
+                    tap_state.output_color(output).unwrap_or(Color::BLACK),
+                    &mut materials,
+                )),
+                Transform::from_xyz(tap_config.outlet[0], tap_config.outlet[1] - SOCKET_RADIUS - 8., 6.)
+                    .with_scale(Vec2::splat(LIGHTNESS_INDICATOR_SIZE).extend(1.)),
+            ));
+
+            //reservoir gauge sits above the outlet
+            let gauge_bottom = tap_config.outlet[1] + SOCKET_RADIUS + 4.;
+            parent.spawn((
+                Mesh2d(gauge_mesh.clone()),
+                MeshMaterial2d(socket_material.clone()),
+                Transform::from_xyz(tap_config.outlet[0], gauge_bottom + RESERVOIR_GAUGE_HEIGHT / 2., 5.)
+                    .with_scale(Vec2::new(RESERVOIR_GAUGE_WIDTH + 2., RESERVOIR_GAUGE_HEIGHT + 2.).extend(1.)),
+            ));
+            parent.spawn((
+                ReservoirGauge {
+                    output,
+                    bottom: gauge_bottom,
+                },
+                Mesh2d(gauge_mesh.clone()),
+                MeshMaterial2d(tap_color),
+                Transform::from_xyz(tap_config.outlet[0], gauge_bottom + RESERVOIR_GAUGE_HEIGHT / 2., 6.)
+                    .with_scale(Vec2::new(RESERVOIR_GAUGE_WIDTH, RESERVOIR_GAUGE_HEIGHT).extend(1.)),
+            ));
+        }
+
+        for ((input, output), mixer_config) in tap_state.mixer_ids().into_iter().zip(tap_list.mixers.iter()) {
+            let inlet = Vec2::from(mixer_config.inlet);
+            let outlet = Vec2::from(mixer_config.outlet);
+            let middle = (inlet + outlet) / 2.;
+            parent.spawn((
+                Mixer,
+                Mesh2d(mixer_mesh.clone()),
+                MeshMaterial2d(mixer_material.clone()),
+                Transform::from_xyz(middle.x, middle.y, 5.)
+                    .with_scale(Vec2::new(inlet.distance(outlet), 1.).extend(1.)),
+                Name::new(mixer_config.name.clone()),
+            ));
+            parent.spawn((
+                InputSocket(input),
+                Mesh2d(socket_mesh.clone()),
+                MeshMaterial2d(socket_material.clone()),
+                Transform::from_xyz(inlet.x, inlet.y, 6.),
+            ));
+            parent.spawn((
+                OutputSocket(output),
+                Mesh2d(socket_mesh.clone()),
+                MeshMaterial2d(socket_material.clone()),
+                Transform::from_xyz(outlet.x, outlet.y, 6.),
+            ));
+        }
     });
 }
 
+const RESERVOIR_GAUGE_WIDTH: f32 = 8.;
+const RESERVOIR_GAUGE_HEIGHT: f32 = 30.;
+
+/// fill of the gauge showing how much liquid is left in an output's reservoir
+#[derive(Component, Debug)]
+struct ReservoirGauge {
+    output: OutputId,
+    bottom: f32,
+}
+
+fn update_reservoir_gauges(
+    tap_state: Res<TapState>,
+    mut gauges: Query<(&ReservoirGauge, &mut Transform)>,
+) {
+    for (gauge, mut transform) in gauges.iter_mut() {
+        let Some(level) = tap_state.reservoir_level(gauge.output) else {
+            continue;
+        };
+        let height = RESERVOIR_GAUGE_HEIGHT * level;
+        transform.scale.y = height;
+        transform.translation.y = gauge.bottom + height / 2.;
+    }
+}
+
+const LIGHTNESS_INDICATOR_SIZE: f32 = 8.;
+
+/// swatch showing the color an output pours at its current dilution
+#[derive(Component, Debug)]
+struct LightnessIndicator(OutputId);
+
+fn update_lightness_indicators(
+    mut lightness_changes: EventReader<LightnessChanged>,
+    tap_state: Res<TapState>,
+    mut indicators: Query<(&LightnessIndicator, &mut MeshMaterial2d<ColorMaterial>)>,
+    mut drop_materials: ResMut<DropMaterials>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    for change in lightness_changes.read() {
+        let Some(color) = tap_state.output_color(change.output) else {
+            continue;
+        };
+        for (indicator, mut material) in indicators.iter_mut() {
+            if indicator.0 == change.output {
+                material.0 = drop_materials.get_or_add(color, &mut materials);
+            }
+        }
+    }
+}
+
 #[derive(Component, Debug, Default)]
 pub struct Tap;
 
@@ -198,4 +398,4 @@ pub struct Tap;
 struct Mixer;
 
 #[derive(Component, Debug)]
-struct Input(DrinkInput);
+struct Input(InputId);
@@ -0,0 +1,236 @@
+use bevy::color::{color_difference::EuclideanDistance, Color, Mix, Oklaba, Srgba};
+
+/// liquid colors are stored with components from 0 to 255, so white has to be on the same scale
+pub const COLOR_WHITE: Color = Color::linear_rgb(255., 255., 255.);
+
+/// thins a color out towards white, 0 leaves it as is and 1 is pure white
+pub fn dilute(color: Color, lightness: f32) -> Color {
+    color.mix(&COLOR_WHITE, lightness.clamp(0., 1.))
+}
+
+/// how different two colors look, measured as their distance in oklab.
+/// around 0.02 is barely noticeable, the primaries are around 0.5 apart
+pub fn perceptual_distance(color1: Color, color2: Color) -> f32 {
+    to_oklab(color1).distance(&to_oklab(color2))
+}
+
+/// reads the 0 to 255 components as the rgb values a designer would pick them as
+fn to_srgba(color: Color) -> Srgba {
+    let color = color.to_linear();
+    Srgba::rgb(
+        (color.red / 255.).clamp(0., 1.),
+        (color.green / 255.).clamp(0., 1.),
+        (color.blue / 255.).clamp(0., 1.),
+    )
+}
+
+/// liquid color scaled down for materials and ui, which clamp anything past 1 to white
+pub fn display_color(color: Color) -> Color {
+    Color::Srgba(to_srgba(color))
+}
+
+fn to_oklab(color: Color) -> Oklaba {
+    Oklaba::from(to_srgba(color))
+}
+
+/// mixes colors like paint by averaging them in red-yellow-blue space, so red and yellow
+/// make orange and blue and yellow make green instead of the grey an rgb average gives.
+/// returns None when there is nothing to mix
+pub fn mix_pigments<'a>(colors: impl Iterator<Item = &'a Color>) -> Option<Color> {
+    let mut total = [0.; 3];
+    let mut total_intensity = 0.;
+    let mut count = 0;
+
+    for color in colors {
+        let linear = color.to_linear();
+        let ryb = rgb_to_ryb([linear.red, linear.green, linear.blue]);
+        for (sum, component) in total.iter_mut().zip(ryb) {
+            *sum += component;
+        }
+        total_intensity += max_component(ryb);
+        count += 1;
+    }
+
+    if count == 0 {
+        return None;
+    }
+
+    //averaging washes the result out, scale it back up to the average strength of the inputs
+    let mut mixed = total.map(|component| component / count as f32);
+    let mixed_max = max_component(mixed);
+    if mixed_max > 0. {
+        let scale = total_intensity / count as f32 / mixed_max;
+        mixed = mixed.map(|component| component * scale);
+    }
+
+    let [red, green, blue] = ryb_to_rgb(mixed);
+    Some(Color::linear_rgb(red, green, blue))
+}
+
+fn max_component(color: [f32; 3]) -> f32 {
+    color[0].max(color[1]).max(color[2])
+}
+
+fn min_component(color: [f32; 3]) -> f32 {
+    color[0].min(color[1]).min(color[2])
+}
+
+/// Sugita and Takahashi's rgb to ryb conversion
+fn rgb_to_ryb(rgb: [f32; 3]) -> [f32; 3] {
+    let white = min_component(rgb);
+    let [mut red, mut green, mut blue] = rgb.map(|component| component - white);
+    let max_green = max_component([red, green, blue]);
+
+    //yellow is the part shared by red and green
+    let mut yellow = red.min(green);
+    red -= yellow;
+    green -= yellow;
+
+    if blue > 0. && green > 0. {
+        blue /= 2.;
+        green /= 2.;
+    }
+    yellow += green;
+    blue += green;
+
+    let max_yellow = max_component([red, yellow, blue]);
+    if max_yellow > 0. {
+        let scale = max_green / max_yellow;
+        red *= scale;
+        yellow *= scale;
+        blue *= scale;
+    }
+
+    [red + white, yellow + white, blue + white]
+}
+
+/// inverse of rgb_to_ryb
+fn ryb_to_rgb(ryb: [f32; 3]) -> [f32; 3] {
+    let white = min_component(ryb);
+    let [mut red, mut yellow, mut blue] = ryb.map(|component| component - white);
+    let max_yellow = max_component([red, yellow, blue]);
+
+    //green is the part shared by yellow and blue
+    let mut green = yellow.min(blue);
+    yellow -= green;
+    blue -= green;
+
+    if blue > 0. && green > 0. {
+        blue *= 2.;
+        green *= 2.;
+    }
+    red += yellow;
+    green += yellow;
+
+    let max_green = max_component([red, green, blue]);
+    if max_green > 0. {
+        let scale = max_yellow / max_green;
+        red *= scale;
+        green *= scale;
+        blue *= scale;
+    }
+
+    [red + white, green + white, blue + white]
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::color::{Hsla, Hue};
+
+    use super::*;
+    use crate::game::{orders::order_config::default_color_tolerance, taps::tap_config::TapList};
+
+    fn tap_colors() -> Vec<[f32; 3]> {
+        let tap_list: TapList = toml::from_str(include_str!("../../../assets/taps.toml")).unwrap();
+        tap_list.taps.iter().map(|tap| tap.color).collect()
+    }
+
+    /// hue in degrees, reading the components the same way to_oklab does
+    fn hue(color: Color) -> f32 {
+        Hsla::from(to_srgba(color)).hue()
+    }
+
+    fn mix(color1: [f32; 3], color2: [f32; 3]) -> Color {
+        let colors = [color1, color2].map(|[red, green, blue]| Color::linear_rgb(red, green, blue));
+        mix_pigments(colors.iter()).unwrap()
+    }
+
+    /// the red, blue and yellow taps, in the order taps.toml lists them
+    fn primaries() -> [[f32; 3]; 3] {
+        tap_colors().try_into().expect("taps.toml should have a red, blue and yellow tap")
+    }
+
+    #[test]
+    fn red_and_yellow_make_orange() {
+        let [red, _, yellow] = primaries();
+        let hue = hue(mix(red, yellow));
+        assert!((15. ..=45.).contains(&hue), "hue {hue}");
+    }
+
+    #[test]
+    fn blue_and_yellow_make_green() {
+        let [_, blue, yellow] = primaries();
+        let hue = hue(mix(blue, yellow));
+        assert!((90. ..=150.).contains(&hue), "hue {hue}");
+    }
+
+    #[test]
+    fn red_and_blue_make_purple() {
+        let [red, blue, _] = primaries();
+        let hue = hue(mix(red, blue));
+        assert!((260. ..=320.).contains(&hue), "hue {hue}");
+    }
+
+    #[test]
+    fn mixing_nothing_gives_nothing() {
+        assert!(mix_pigments([].iter()).is_none());
+    }
+
+    #[test]
+    fn ryb_round_trips_tap_colors() {
+        for color in tap_colors() {
+            let round_trip = ryb_to_rgb(rgb_to_ryb(color));
+            for (component, expected) in round_trip.iter().zip(color) {
+                assert!((component - expected).abs() < 0.01, "{color:?} came back as {round_trip:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn tap_colors_are_told_apart() {
+        let colors: Vec<Color> = tap_colors()
+            .into_iter()
+            .map(|[red, green, blue]| Color::linear_rgb(red, green, blue))
+            .collect();
+        for (index, color1) in colors.iter().enumerate() {
+            for color2 in colors.iter().skip(index + 1) {
+                let distance = perceptual_distance(*color1, *color2);
+                assert!(distance > default_color_tolerance(), "{color1:?} and {color2:?} are {distance} apart");
+            }
+        }
+    }
+
+    #[test]
+    fn color_matches_itself() {
+        for [red, green, blue] in tap_colors() {
+            let color = Color::linear_rgb(red, green, blue);
+            assert_eq!(perceptual_distance(color, color), 0.);
+        }
+    }
+
+    #[test]
+    fn pink_is_not_red() {
+        let [[red, green, blue], ..] = primaries();
+        let red = Color::linear_rgb(red, green, blue);
+        assert!(perceptual_distance(red, dilute(red, 0.5)) > default_color_tolerance());
+    }
+
+    #[test]
+    fn pink_displays_as_pink() {
+        let [[red, green, blue], ..] = primaries();
+        let red = Color::linear_rgb(red, green, blue);
+        let pink = display_color(dilute(red, 0.5)).to_srgba();
+        assert_eq!(pink.red, 1.);
+        assert!(pink.green < 0.6 && pink.blue < 0.6, "{pink:?}");
+    }
+}
@@ -0,0 +1,239 @@
+use std::collections::HashMap;
+
+use bevy::{
+    asset::{Assets, Handle},
+    color::Color,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        query::Added,
+        system::{Commands, Query, ResMut, Resource},
+    },
+    math::primitives::Circle,
+    render::{
+        mesh::{Mesh, Mesh2d},
+        view::Visibility,
+    },
+    sprite::{ColorMaterial, MeshMaterial2d},
+    transform::components::Transform,
+};
+use bevy_rapier2d::prelude::{
+    Collider, ColliderDisabled, GravityScale, RigidBody, RigidBodyDisabled, Velocity,
+};
+
+use crate::game::GameScreen;
+
+use super::{color_mixing::display_color, ColorDrop, DropSource, OutputId};
+
+pub const DROP_RADIUS: f32 = 2.;
+/// released drops past this many are despawned instead of kept for reuse
+const MAX_POOLED_DROPS: usize = 1024;
+
+pub fn setup_drop_pool(mut commands: Commands, mut meshes: ResMut<Assets<Mesh>>) {
+    commands.insert_resource(DropAssets {
+        mesh: meshes.add(Circle::new(DROP_RADIUS)),
+    });
+    commands.insert_resource(DropMaterials::default());
+    commands.insert_resource(DropPool::default());
+}
+
+/// mesh shared by every drop
+#[derive(Resource)]
+pub struct DropAssets {
+    pub mesh: Handle<Mesh>,
+}
+
+/// one material per liquid color, shared by drops and cup fills
+#[derive(Resource, Default)]
+pub struct DropMaterials(HashMap<[i32; 3], Handle<ColorMaterial>>);
+
+impl DropMaterials {
+    pub fn get_or_add(
+        &mut self,
+        color: Color,
+        materials: &mut Assets<ColorMaterial>,
+    ) -> Handle<ColorMaterial> {
+        self.0
+            .entry(color_key(color))
+            .or_insert_with(|| materials.add(display_color(color)))
+            .clone()
+    }
+}
+
+/// colors are bucketed to whole units so blended mixer colors that look the same share a key
+pub fn color_key(color: Color) -> [i32; 3] {
+    let linear = color.to_linear();
+    [
+        linear.red.round() as i32,
+        linear.green.round() as i32,
+        linear.blue.round() as i32,
+    ]
+}
+
+/// drop that has been released back to the pool, hidden and out of the physics world
+#[derive(Component, Debug)]
+pub struct PooledDrop;
+
+#[derive(Resource, Default)]
+pub struct DropPool {
+    free: Vec<Entity>,
+}
+
+/// everything that differs between one poured drop and the next
+pub struct NewDrop {
+    pub material: Handle<ColorMaterial>,
+    pub color: Color,
+    pub source: OutputId,
+    pub transform: Transform,
+    pub velocity: Velocity,
+}
+
+impl DropPool {
+    /// reuses a released drop if one is available, otherwise spawns a new one
+    pub fn spawn(&mut self, commands: &mut Commands, drop_assets: &DropAssets, drop: NewDrop) {
+        let NewDrop {
+            material,
+            color,
+            source,
+            transform,
+            velocity,
+        } = drop;
+        while let Some(entity) = self.free.pop() {
+            if let Some(mut drop) = commands.get_entity(entity) {
+                drop.remove::<(PooledDrop, RigidBodyDisabled, ColliderDisabled)>()
+                    .insert((
+                        transform,
+                        velocity,
+                        MeshMaterial2d(material),
+                        ColorDrop(color),
+                        DropSource(source),
+                        Visibility::Inherited,
+                    ));
+                return;
+            }
+        }
+
+        commands.spawn((
+            Mesh2d(drop_assets.mesh.clone()),
+            MeshMaterial2d(material),
+            transform,
+            ColorDrop(color),
+            DropSource(source),
+            RigidBody::Dynamic,
+            GravityScale(0.4),
+            velocity,
+            Collider::ball(DROP_RADIUS),
+            GameScreen,
+        ));
+    }
+}
+
+/// takes the drop out of play so a later spawn can reuse it
+pub fn release_drop(commands: &mut Commands, drop: Entity) {
+    commands.entity(drop).remove::<ColorDrop>().insert((
+        PooledDrop,
+        RigidBodyDisabled,
+        ColliderDisabled,
+        Visibility::Hidden,
+    ));
+}
+
+/// drops only become reusable once their release has been applied
+pub fn collect_released_drops(
+    mut commands: Commands,
+    mut pool: ResMut<DropPool>,
+    released: Query<Entity, Added<PooledDrop>>,
+) {
+    for drop in released.iter() {
+        if pool.free.len() >= MAX_POOLED_DROPS {
+            commands.entity(drop).despawn();
+        } else {
+            pool.free.push(drop);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::{
+        app::{App, Startup, Update},
+        asset::{AssetApp, AssetPlugin},
+        ecs::{
+            query::{Or, With},
+            schedule::IntoSystemConfigs,
+            system::Res,
+        },
+        MinimalPlugins,
+    };
+
+    use super::*;
+    use crate::game::taps::{tap_config::TapList, TapState};
+
+    const DROPS_PER_FRAME: usize = 100;
+    const FRAMES: usize = 100;
+
+    #[derive(Resource)]
+    struct Source(OutputId);
+
+    fn spawn_drops(
+        mut commands: Commands,
+        source: Res<Source>,
+        mut pool: ResMut<DropPool>,
+        drop_assets: Res<DropAssets>,
+        mut drop_materials: ResMut<DropMaterials>,
+        mut materials: ResMut<Assets<ColorMaterial>>,
+    ) {
+        let colors = [
+            Color::linear_rgb(255., 0., 0.),
+            Color::linear_rgb(255., 255., 0.),
+            Color::linear_rgb(0., 0., 255.),
+        ];
+        for index in 0..DROPS_PER_FRAME {
+            let color = colors[index % colors.len()];
+            pool.spawn(
+                &mut commands,
+                &drop_assets,
+                NewDrop {
+                    material: drop_materials.get_or_add(color, &mut materials),
+                    color,
+                    source: source.0,
+                    transform: Transform::default(),
+                    velocity: Velocity::zero(),
+                },
+            );
+        }
+    }
+
+    fn release_drops(mut commands: Commands, drops: Query<Entity, With<ColorDrop>>) {
+        for drop in drops.iter() {
+            release_drop(&mut commands, drop);
+        }
+    }
+
+    #[test]
+    fn released_drops_are_reused() {
+        let tap_list: TapList = toml::from_str(include_str!("../../../assets/taps.toml")).unwrap();
+        let source = TapState::new(&tap_list).color_output_ids()[0];
+
+        let mut app = App::new();
+        app.insert_resource(Source(source))
+            .add_plugins((MinimalPlugins, AssetPlugin::default()))
+            .init_asset::<Mesh>()
+            .init_asset::<ColorMaterial>()
+            .add_systems(Startup, setup_drop_pool)
+            .add_systems(Update, (spawn_drops, release_drops, collect_released_drops).chain());
+
+        for _ in 0..FRAMES {
+            app.update();
+        }
+
+        let world = app.world_mut();
+        assert_eq!(world.resource::<Assets<Mesh>>().len(), 1);
+        assert_eq!(world.resource::<Assets<ColorMaterial>>().len(), 3);
+        let drops = world
+            .query_filtered::<Entity, Or<(With<ColorDrop>, With<PooledDrop>)>>()
+            .iter(world)
+            .count();
+        assert!(drops <= DROPS_PER_FRAME, "{drops} drop entities for {DROPS_PER_FRAME} live drops");
+    }
+}
@@ -0,0 +1,178 @@
+use bevy::{
+    asset::{Assets, Handle, RenderAssetUsages},
+    color::Color,
+    ecs::{
+        component::Component,
+        entity::Entity,
+        event::EventReader,
+        query::{With, Without},
+        system::{Commands, Query, Res, ResMut, Resource},
+    },
+    hierarchy::DespawnRecursiveExt,
+    math::Vec2,
+    render::mesh::{Mesh, Mesh2d, PrimitiveTopology},
+    sprite::{ColorMaterial, MeshMaterial2d},
+    transform::components::{GlobalTransform, Transform},
+};
+
+use crate::game::GameScreen;
+
+use super::{tap_events::ConnectionChanged, InputId, OutputId, TapState};
+
+const HOSE_WIDTH: f32 = 4.;
+const HOSE_SAG: f32 = 20.;
+const HOSE_SEGMENTS: usize = 24;
+const HOSE_Z: f32 = 8.;
+pub const SOCKET_RADIUS: f32 = 5.;
+
+/// socket on a tap or mixer outlet that a hose can be plugged into
+#[derive(Component, Debug)]
+pub struct OutputSocket(pub OutputId);
+
+/// socket on a tap or mixer inlet that a hose can be plugged into
+#[derive(Component, Debug)]
+pub struct InputSocket(pub InputId);
+
+#[derive(Component, Debug)]
+pub struct Hose {
+    pub output: OutputId,
+    pub input: InputId,
+}
+
+/// the hose shown while a hose is being dragged from an outlet
+#[derive(Component, Debug)]
+pub struct HosePreview;
+
+#[derive(Resource)]
+pub struct HoseMaterial(Handle<ColorMaterial>);
+
+/// outlet a hose is currently being dragged from and where its loose end is
+#[derive(Resource, Debug, Default)]
+pub struct DraggedHose {
+    pub output: Option<OutputId>,
+    pub end: Vec2,
+}
+
+pub fn setup_hoses(mut commands: Commands, mut materials: ResMut<Assets<ColorMaterial>>) {
+    commands.insert_resource(HoseMaterial(
+        materials.add(Color::linear_rgb(0.05, 0.05, 0.05)),
+    ));
+    commands.insert_resource(DraggedHose::default());
+}
+
+/// returns the socket whose circle contains the point
+pub fn socket_at<'a, T>(
+    sockets: impl Iterator<Item = (&'a T, &'a GlobalTransform)>,
+    point: Vec2,
+) -> Option<&'a T> {
+    sockets
+        .filter(|(_, transform)| transform.translation().truncate().distance(point) <= SOCKET_RADIUS + 3.)
+        .map(|(socket, _)| socket)
+        .next()
+}
+
+/// rebuilds the hose meshes whenever a connection is made or removed
+pub fn update_hoses(
+    mut commands: Commands,
+    mut connection_changes: EventReader<ConnectionChanged>,
+    tap_state: Res<TapState>,
+    hoses: Query<Entity, With<Hose>>,
+    output_sockets: Query<(&OutputSocket, &GlobalTransform)>,
+    input_sockets: Query<(&InputSocket, &GlobalTransform)>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    hose_material: Res<HoseMaterial>,
+) {
+    if connection_changes.read().count() == 0 {
+        return;
+    }
+
+    for hose in hoses.iter() {
+        commands.entity(hose).despawn_recursive();
+    }
+
+    for (output, input) in tap_state.connections().iter() {
+        let start = output_sockets
+            .iter()
+            .find(|(socket, _)| socket.0 == *output)
+            .map(|(_, transform)| transform.translation().truncate());
+        let end = input_sockets
+            .iter()
+            .find(|(socket, _)| socket.0 == *input)
+            .map(|(_, transform)| transform.translation().truncate());
+        let (Some(start), Some(end)) = (start, end) else {
+            continue;
+        };
+
+        commands.spawn((
+            Hose {
+                output: *output,
+                input: *input,
+            },
+            Mesh2d(meshes.add(hose_mesh(start, end))),
+            MeshMaterial2d(hose_material.0.clone()),
+            Transform::from_xyz(0., 0., HOSE_Z),
+            GameScreen,
+        ));
+    }
+}
+
+/// keeps the preview hose between the dragged outlet and the cursor
+pub fn update_hose_preview(
+    mut commands: Commands,
+    dragged_hose: Res<DraggedHose>,
+    preview: Query<(Entity, &Mesh2d), With<HosePreview>>,
+    output_sockets: Query<(&OutputSocket, &GlobalTransform), Without<HosePreview>>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    hose_material: Res<HoseMaterial>,
+) {
+    let start = dragged_hose.output.and_then(|output| {
+        output_sockets
+            .iter()
+            .find(|(socket, _)| socket.0 == output)
+            .map(|(_, transform)| transform.translation().truncate())
+    });
+
+    let Some(start) = start else {
+        for (entity, _) in preview.iter() {
+            commands.entity(entity).despawn_recursive();
+        }
+        return;
+    };
+
+    let mesh = hose_mesh(start, dragged_hose.end);
+    match preview.get_single() {
+        Ok((_, mesh_handle)) => {
+            if let Some(preview_mesh) = meshes.get_mut(mesh_handle.0.id()) {
+                *preview_mesh = mesh;
+            }
+        }
+        Err(_) => {
+            commands.spawn((
+                HosePreview,
+                Mesh2d(meshes.add(mesh)),
+                MeshMaterial2d(hose_material.0.clone()),
+                Transform::from_xyz(0., 0., HOSE_Z),
+                GameScreen,
+            ));
+        }
+    }
+}
+
+/// builds a strip along a sagging quadratic curve from start to end, in world coordinates
+pub fn hose_mesh(start: Vec2, end: Vec2) -> Mesh {
+    let sag = HOSE_SAG + start.distance(end) * 0.25;
+    let control = (start + end) / 2. - Vec2::Y * sag;
+
+    let mut positions = Vec::with_capacity((HOSE_SEGMENTS + 1) * 2);
+    for segment in 0..=HOSE_SEGMENTS {
+        let t = segment as f32 / HOSE_SEGMENTS as f32;
+        let point = start.lerp(control, t).lerp(control.lerp(end, t), t);
+        let tangent = ((control - start) * (1. - t) + (end - control) * t).normalize_or(Vec2::X);
+        let offset = tangent.perp() * HOSE_WIDTH / 2.;
+        positions.push((point + offset).extend(0.).to_array());
+        positions.push((point - offset).extend(0.).to_array());
+    }
+
+    Mesh::new(PrimitiveTopology::TriangleStrip, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, positions)
+}
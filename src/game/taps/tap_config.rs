@@ -0,0 +1,122 @@
+use bevy::{asset::Asset, ecs::system::Resource, reflect::TypePath};
+use serde::Deserialize;
+
+use crate::assets::toml_loader::ValidateConfig;
+
+#[derive(Deserialize, Resource, Asset, TypePath, Clone)]
+pub struct TapList {
+    pub taps: Vec<TapConfig>,
+    #[serde(default)]
+    pub mixers: Vec<MixerConfig>,
+}
+
+impl ValidateConfig for TapList {
+    fn validate(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.taps.is_empty() {
+            problems.push("taps is empty, there needs to be at least one tap".to_owned());
+        }
+
+        for (index, tap) in self.taps.iter().enumerate() {
+            let tap_key = format!("taps[{index}] ({})", tap.name);
+            if tap.color.iter().any(|component| !(0. ..=255.).contains(component)) {
+                problems.push(format!("{tap_key}: color {:?} has components outside 0 to 255", tap.color));
+            }
+            if !tap.flow_rate.is_finite() || tap.flow_rate <= 0. {
+                problems.push(format!("{tap_key}: flow_rate must be above 0"));
+            }
+            if tap.reservoir == 0 {
+                problems.push(format!("{tap_key}: reservoir must be at least 1"));
+            }
+            if !tap.refill_time.is_finite() || tap.refill_time < 0. {
+                problems.push(format!("{tap_key}: refill_time can't be negative"));
+            }
+            problems.extend(tap.pour.validate(&tap_key));
+        }
+
+        for (index, mixer) in self.mixers.iter().enumerate() {
+            let mixer_key = format!("mixers[{index}] ({})", mixer.name);
+            if !mixer.flow_rate.is_finite() || mixer.flow_rate <= 0. {
+                problems.push(format!("{mixer_key}: flow_rate must be above 0"));
+            }
+            problems.extend(mixer.pour.validate(&mixer_key));
+        }
+
+        problems
+    }
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct TapConfig {
+    pub name: String,
+    pub color: [f32; 3],
+    pub position: [f32; 2],
+    pub outlet: [f32; 2],
+    pub flow_rate: f32,
+    /// drops the tap holds before it needs refilling
+    pub reservoir: usize,
+    /// seconds a refill takes
+    pub refill_time: f32,
+    #[serde(default)]
+    pub pour: PourConfig,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct MixerConfig {
+    pub name: String,
+    pub inlet: [f32; 2],
+    pub outlet: [f32; 2],
+    #[serde(default = "default_flow_rate")]
+    pub flow_rate: f32,
+    #[serde(default)]
+    pub pour: PourConfig,
+}
+
+/// how pressing the pour button on an output behaves
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+pub struct PourConfig {
+    pub mode: PourMode,
+    /// presses that can be queued up while the output is cooling down
+    pub burst_size: usize,
+    /// seconds between queued presses being poured
+    pub cooldown: f32,
+}
+
+impl PourConfig {
+    fn validate(&self, output_key: &str) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.burst_size == 0 {
+            problems.push(format!("{output_key}: pour.burst_size must be at least 1"));
+        }
+        if !self.cooldown.is_finite() || self.cooldown < 0. {
+            problems.push(format!("{output_key}: pour.cooldown can't be negative"));
+        }
+
+        problems
+    }
+}
+
+impl Default for PourConfig {
+    fn default() -> Self {
+        Self {
+            mode: PourMode::Drip,
+            burst_size: 2,
+            cooldown: 0.25,
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum PourMode {
+    /// every press pours a single drop
+    #[default]
+    Drip,
+    /// pours for as long as the button is held
+    Hold,
+}
+
+fn default_flow_rate() -> f32 {
+    5.
+}
@@ -0,0 +1,102 @@
+use bevy::{
+    color::Color,
+    ecs::{
+        event::{Event, EventWriter},
+        system::ResMut,
+    },
+};
+
+use super::{InputId, OutputId, TapState};
+
+/// an output's hose was plugged into an input, or pulled out when input is None
+#[derive(Event, Debug, Clone, Copy)]
+pub struct ConnectionChanged {
+    pub output: OutputId,
+    pub input: Option<InputId>,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct OutputSwitched {
+    pub output: OutputId,
+    pub on: bool,
+}
+
+/// a drop left an output, either falling from a tap or flowing into a mixer
+#[derive(Event, Debug, Clone, Copy)]
+pub struct DropDispensed {
+    pub output: OutputId,
+    pub color: Color,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MixerToggled {
+    pub output: OutputId,
+    pub on: bool,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct FlowRateChanged {
+    pub output: OutputId,
+    pub flow_rate: f32,
+}
+
+/// how far a color output's liquid is diluted towards white changed
+#[derive(Event, Debug, Clone, Copy)]
+pub struct LightnessChanged {
+    pub output: OutputId,
+    pub lightness: f32,
+}
+
+#[derive(Event, Debug, Clone, Copy)]
+pub struct RefillStarted {
+    pub output: OutputId,
+}
+
+/// changes recorded by TapState's methods until send_tap_events sends them
+#[derive(Debug, Clone, Copy)]
+pub enum TapEvent {
+    ConnectionChanged(ConnectionChanged),
+    OutputSwitched(OutputSwitched),
+    DropDispensed(DropDispensed),
+    MixerToggled(MixerToggled),
+    FlowRateChanged(FlowRateChanged),
+    LightnessChanged(LightnessChanged),
+    RefillStarted(RefillStarted),
+}
+
+pub fn send_tap_events(
+    mut tap_state: ResMut<TapState>,
+    mut connection_changed: EventWriter<ConnectionChanged>,
+    mut output_switched: EventWriter<OutputSwitched>,
+    mut drop_dispensed: EventWriter<DropDispensed>,
+    mut mixer_toggled: EventWriter<MixerToggled>,
+    mut flow_rate_changed: EventWriter<FlowRateChanged>,
+    mut lightness_changed: EventWriter<LightnessChanged>,
+    mut refill_started: EventWriter<RefillStarted>,
+) {
+    for event in tap_state.drain_events() {
+        match event {
+            TapEvent::ConnectionChanged(event) => {
+                connection_changed.send(event);
+            }
+            TapEvent::OutputSwitched(event) => {
+                output_switched.send(event);
+            }
+            TapEvent::DropDispensed(event) => {
+                drop_dispensed.send(event);
+            }
+            TapEvent::MixerToggled(event) => {
+                mixer_toggled.send(event);
+            }
+            TapEvent::FlowRateChanged(event) => {
+                flow_rate_changed.send(event);
+            }
+            TapEvent::LightnessChanged(event) => {
+                lightness_changed.send(event);
+            }
+            TapEvent::RefillStarted(event) => {
+                refill_started.send(event);
+            }
+        }
+    }
+}
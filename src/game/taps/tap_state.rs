@@ -4,7 +4,8 @@ use std::{
 };
 
 use bevy::{
-    color::{Color, Mix},
+    asset::Assets,
+    color::Color,
     ecs::{
         component::Component,
         system::{Commands, Res, ResMut, Resource},
@@ -12,14 +13,34 @@ use bevy::{
     time::{Time, Timer, Virtual},
 };
 
-pub const COLOR_RED: Color = Color::linear_rgb(255., 0., 0.);
-pub const COLOR_BLUE: Color = Color::linear_rgb(0.0, 0., 255.);
-pub const COLOR_YELLOW: Color = Color::linear_rgb(234., 255., 0.);
+use crate::assets::OrderAssets;
 
-const MAX_PENDING_DROPS: usize = 2;
+use super::{
+    color_mixing::{dilute, mix_pigments},
+    tap_config::{PourConfig, PourMode, TapConfig, TapList},
+    tap_events::{
+        ConnectionChanged, DropDispensed, FlowRateChanged, LightnessChanged, MixerToggled,
+        OutputSwitched, RefillStarted, TapEvent,
+    },
+};
 
-pub fn add_tap_state(mut commands: Commands) {
-    commands.insert_resource(TapState::new());
+const MIXER_CAPACITY: usize = 64;
+/// drops per second
+pub const MIN_FLOW_RATE: f32 = 1.;
+pub const MAX_FLOW_RATE: f32 = 20.;
+
+pub fn add_tap_state(
+    mut commands: Commands,
+    order_asset: Res<OrderAssets>,
+    tap_lists: Res<Assets<TapList>>,
+) {
+    let tap_list = tap_lists
+        .get(order_asset.tap_config.id())
+        .expect("taps.toml is missing")
+        .clone();
+
+    commands.insert_resource(TapState::new(&tap_list));
+    commands.insert_resource(tap_list);
 }
 
 pub fn timers(time: Res<Time<Virtual>>, mut tap_state: ResMut<TapState>) {
@@ -28,159 +49,440 @@ pub fn timers(time: Res<Time<Virtual>>, mut tap_state: ResMut<TapState>) {
 
 #[derive(Resource, Debug)]
 pub struct TapState {
-    connections: HashMap<DrinkOutput, Option<DrinkInput>>,
-    outputs: HashMap<DrinkOutput, OutputState>,
-    pub speed: TapSpeed,
+    connections: HashMap<OutputId, Option<InputId>>,
+    outputs: HashMap<OutputId, OutputState>,
+    inputs: HashMap<InputId, InputType>,
+    /// changes waiting to be sent as bevy events
+    events: Vec<TapEvent>,
 }
 
 impl TapState {
-    pub fn new() -> TapState {
-        let mut connections = HashMap::with_capacity(5);
-        connections.insert(DrinkOutput::Color1, Option::None);
-        connections.insert(DrinkOutput::Color2, Option::None);
-        connections.insert(DrinkOutput::Color3, Option::None);
-        connections.insert(DrinkOutput::Mixer1, Option::None);
-        connections.insert(DrinkOutput::Mixer2, Option::None);
-
-        let mut outputs = HashMap::with_capacity(5);
-        outputs.insert(
-            DrinkOutput::Color1,
-            OutputState::new_color(COLOR_RED.clone()),
-        ); //red
-        outputs.insert(
-            DrinkOutput::Color2,
-            OutputState::new_color(COLOR_BLUE.clone()),
-        ); //blue
-        outputs.insert(
-            DrinkOutput::Color3,
-            OutputState::new_color(COLOR_YELLOW.clone()),
-        ); //yellow
-        outputs.insert(DrinkOutput::Mixer1, OutputState::new_mixer());
-        outputs.insert(DrinkOutput::Mixer2, OutputState::new_mixer());
+    /// registers an output and an input for every tap and mixer in the config.
+    /// ids are handed out in config order, taps first then mixers
+    pub fn new(tap_list: &TapList) -> TapState {
+        let output_count = tap_list.taps.len() + tap_list.mixers.len();
+        let mut connections = HashMap::with_capacity(output_count);
+        let mut outputs = HashMap::with_capacity(output_count);
+        let mut inputs = HashMap::with_capacity(output_count);
+
+        for (index, tap_config) in tap_list.taps.iter().enumerate() {
+            let output = OutputId(index);
+            connections.insert(output, Option::None);
+            outputs.insert(
+                output,
+                OutputState::new_color(tap_config),
+            );
+            inputs.insert(InputId(index), InputType::Tap);
+        }
+
+        for (index, mixer_config) in tap_list.mixers.iter().enumerate() {
+            let output = OutputId(tap_list.taps.len() + index);
+            connections.insert(output, Option::None);
+            outputs.insert(
+                output,
+                OutputState::new_mixer(
+                    mixer_config.name.clone(),
+                    mixer_config.flow_rate,
+                    &mixer_config.pour,
+                ),
+            );
+            inputs.insert(InputId(tap_list.taps.len() + index), InputType::Mixer(output));
+        }
 
         TapState {
             connections,
             outputs,
-            speed: TapSpeed::Medium
+            inputs,
+            events: Vec::new(),
+        }
+    }
+
+    /// every registered output, sorted by id
+    pub fn output_ids(&self) -> Vec<OutputId> {
+        let mut ids: Vec<OutputId> = self.outputs.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    /// every registered input, sorted by id
+    pub fn input_ids(&self) -> Vec<InputId> {
+        let mut ids: Vec<InputId> = self.inputs.keys().copied().collect();
+        ids.sort();
+        ids
+    }
+
+    /// inputs that pour into cups, in the same order as the taps in taps.toml
+    pub fn tap_ids(&self) -> Vec<InputId> {
+        self.input_ids()
+            .into_iter()
+            .filter(|input| self.inputs.get(input) == Some(&InputType::Tap))
+            .collect()
+    }
+
+    /// color outputs, in the same order as the taps in taps.toml
+    pub fn color_output_ids(&self) -> Vec<OutputId> {
+        self.output_ids()
+            .into_iter()
+            .filter(|output| {
+                matches!(
+                    self.outputs.get(output).map(|output_state| &output_state.output_type),
+                    Some(OutputType::Color(_))
+                )
+            })
+            .collect()
+    }
+
+    /// the input and output of every mixer, in the same order as the mixers in taps.toml
+    pub fn mixer_ids(&self) -> Vec<(InputId, OutputId)> {
+        self.input_ids()
+            .into_iter()
+            .filter_map(|input| Some((input, self.mixer_output(input)?)))
+            .collect()
+    }
+
+    /// every output that is currently plugged into an input, sorted by output
+    pub fn connections(&self) -> Vec<(OutputId, InputId)> {
+        let mut connections: Vec<(OutputId, InputId)> = self
+            .connections
+            .iter()
+            .filter_map(|(output, input_option)| Some((*output, (*input_option)?)))
+            .collect();
+        connections.sort();
+        connections
+    }
+
+    /// the output a mixer input fills, or None for inputs that pour straight into a cup
+    pub fn mixer_output(&self, input: InputId) -> Option<OutputId> {
+        match self.inputs.get(&input) {
+            Some(InputType::Mixer(output)) => Some(*output),
+            _ => None,
         }
     }
 
-    pub fn make_connection(&mut self, output: DrinkOutput, input: DrinkInput) {
-        for (output, input_option) in self.connections.iter_mut() {
+    pub fn make_connection(&mut self, output: OutputId, input: InputId) {
+        if !self.outputs.contains_key(&output) || !self.inputs.contains_key(&input) {
+            return;
+        }
+        //a mixer can't feed itself
+        if self.mixer_output(input) == Some(output) {
+            return;
+        }
+        if self.connections.get(&output) == Some(&Option::Some(input)) {
+            return;
+        }
+        for (stored_output, input_option) in self.connections.iter_mut() {
             if let Some(stored_input) = input_option {
                 if *stored_input == input {
                     *input_option = Option::None;
+                    self.events.push(TapEvent::ConnectionChanged(ConnectionChanged {
+                        output: *stored_output,
+                        input: Option::None,
+                    }));
                 }
             }
         }
-        self.connections
-            .insert(output.clone(), Option::Some(input.clone()));
+        self.connections.insert(output, Option::Some(input));
+        self.events.push(TapEvent::ConnectionChanged(ConnectionChanged {
+            output,
+            input: Option::Some(input),
+        }));
+    }
+
+    /// unplugs the output's hose, switching the output off and dropping any queued presses
+    pub fn disconnect(&mut self, output: OutputId) {
+        let Some(input_option) = self.connections.get_mut(&output) else {
+            return;
+        };
+        if input_option.take().is_some() {
+            self.events.push(TapEvent::ConnectionChanged(ConnectionChanged {
+                output,
+                input: Option::None,
+            }));
+        }
+        self.output_switch(false, output);
+        if let Some(output_state) = self.outputs.get_mut(&output) {
+            output_state.pending_presses = 0;
+        }
+    }
+
+    pub fn disconnect_all(&mut self) {
+        for output in self.output_ids() {
+            self.disconnect(output);
+        }
+    }
+
+    /// the pour button was pressed for the output, drip outputs queue a drop and hold outputs switch on
+    pub fn pour_pressed(&mut self, output: OutputId) {
+        let Some(output_state) = self.outputs.get_mut(&output) else {
+            return;
+        };
+        match output_state.pour_mode {
+            PourMode::Drip => output_state.queue_press(),
+            PourMode::Hold => self.output_switch(true, output),
+        }
     }
 
-    pub fn disconnect(&mut self, output: DrinkOutput) {
-        self.connections.insert(output, Option::None);
+    /// the pour button was let go, hold outputs stop pouring
+    pub fn pour_released(&mut self, output: OutputId) {
+        if self.outputs.get(&output).map(|output_state| output_state.pour_mode) == Some(PourMode::Hold) {
+            self.output_switch(false, output);
+        }
     }
 
-    pub fn drop_pressed(&mut self, output: DrinkOutput) {
-        self.outputs.entry(output).and_modify(|output_state| {
-            if output_state.pending_presses < MAX_PENDING_DROPS {
-                output_state.pending_presses += 1
+    pub fn output_switch(&mut self, switch_on: bool, output: OutputId) {
+        if let Some(output_state) = self.outputs.get_mut(&output) {
+            if output_state.on != switch_on {
+                output_state.on = switch_on;
+                self.events.push(TapEvent::OutputSwitched(OutputSwitched {
+                    output,
+                    on: switch_on,
+                }));
             }
-        });
+        }
     }
 
-    pub fn output_switch(&mut self, switch_on: bool, output: DrinkOutput) {
-        self.outputs.entry(output).and_modify(|output_state| {
-            output_state.on = switch_on;
-        });
+    /// sets how many drops per second the output pours, clamped to the allowed range
+    pub fn set_flow_rate(&mut self, output: OutputId, flow_rate: f32) {
+        if let Some(output_state) = self.outputs.get_mut(&output) {
+            let flow_rate = flow_rate.clamp(MIN_FLOW_RATE, MAX_FLOW_RATE);
+            if output_state.flow_rate != flow_rate {
+                output_state.flow_rate = flow_rate;
+                self.events.push(TapEvent::FlowRateChanged(FlowRateChanged {
+                    output,
+                    flow_rate,
+                }));
+            }
+        }
     }
 
-    pub fn mixer_switch(&mut self, switch_on: bool, output: DrinkOutput) {
-        self.outputs
-            .entry(output)
-            .and_modify(|output_state| match output_state.output_type {
-                OutputType::Color(_) => {}
-                OutputType::Mixer(ref mut mixer_output_state) => {
-                    mixer_output_state.mixer_on = switch_on;
-                }
-            });
+    pub fn adjust_flow_rate(&mut self, output: OutputId, change: f32) {
+        if let Some(flow_rate) = self.outputs.get(&output).map(|output_state| output_state.flow_rate) {
+            self.set_flow_rate(output, flow_rate + change);
+        }
+    }
+
+    /// sets how far the output's liquid is diluted towards white, from 0 to 1
+    pub fn set_lightness(&mut self, output: OutputId, lightness: f32) {
+        let lightness = lightness.clamp(0., 1.);
+        if let Some(OutputType::Color(color_state)) =
+            self.outputs.get_mut(&output).map(|output_state| &mut output_state.output_type)
+        {
+            if color_state.light != lightness {
+                color_state.light = lightness;
+                self.events.push(TapEvent::LightnessChanged(LightnessChanged {
+                    output,
+                    lightness,
+                }));
+            }
+        }
+    }
+
+    pub fn adjust_lightness(&mut self, output: OutputId, change: f32) {
+        if let Some(OutputType::Color(color_state)) =
+            self.outputs.get(&output).map(|output_state| &output_state.output_type)
+        {
+            let lightness = color_state.light + change;
+            self.set_lightness(output, lightness);
+        }
+    }
+
+    /// the color the output's next drop will be, None for mixers
+    pub fn output_color(&self, output: OutputId) -> Option<Color> {
+        match self.outputs.get(&output).map(|output_state| &output_state.output_type) {
+            Some(OutputType::Color(color_state)) => Some(color_state.color()),
+            _ => None,
+        }
+    }
+
+    pub fn mixer_switch(&mut self, switch_on: bool, output: OutputId) {
+        if let Some(mixer_output_state) = self.outputs.get_mut(&output).and_then(|output_state| output_state.mixer_state_mut()) {
+            if mixer_output_state.mixer_on != switch_on {
+                mixer_output_state.mixer_on = switch_on;
+                self.events.push(TapEvent::MixerToggled(MixerToggled {
+                    output,
+                    on: switch_on,
+                }));
+            }
+        }
+    }
+
+    /// the output stops pouring until its reservoir has finished refilling
+    pub fn start_refill(&mut self, output: OutputId) {
+        if let Some(OutputType::Color(color_state)) =
+            self.outputs.get_mut(&output).map(|output_state| &mut output_state.output_type)
+        {
+            if color_state.start_refill() {
+                self.events.push(TapEvent::RefillStarted(RefillStarted { output }));
+            }
+        }
+    }
+
+    pub fn drain_events(&mut self) -> std::vec::Drain<'_, TapEvent> {
+        self.events.drain(..)
+    }
+
+    /// how full the output's reservoir is from 0 to 1, None for outputs without a reservoir
+    pub fn reservoir_level(&self, output: OutputId) -> Option<f32> {
+        match self.outputs.get(&output).map(|output_state| &output_state.output_type) {
+            Some(OutputType::Color(color_state)) => Some(color_state.level()),
+            _ => None,
+        }
     }
 
     pub fn tick(&mut self, time: &Time<Virtual>) {
         for output_state in self.outputs.values_mut() {
             output_state.tick(time);
         }
+        self.fill_mixers(time);
     }
 
-    pub fn get_output_state(&mut self, input: DrinkInput) -> Option<&mut OutputState> {
-        for (output, input_option) in self.connections.iter_mut() {
-            if let Some(stored_input) = input_option {
-                if input == *stored_input {
-                    return self.outputs.get_mut(&output.clone());
+    /// moves drops from every output plugged into a mixer input into that mixer's queue
+    fn fill_mixers(&mut self, time: &Time<Virtual>) {
+        let mixer_connections: Vec<(OutputId, OutputId)> = self
+            .connections
+            .iter()
+            .filter_map(|(output, input_option)| {
+                let mixer = self.mixer_output((*input_option)?)?;
+                Some((*output, mixer))
+            })
+            .collect();
+
+        for (source, mixer) in mixer_connections {
+            let Some(mixer_state) = self.outputs.get_mut(&mixer).and_then(|output_state| output_state.mixer_state_mut()) else {
+                continue;
+            };
+            mixer_state.fill_timer.tick(time.delta());
+            if !mixer_state.fill_timer.finished() || mixer_state.is_full() {
+                continue;
+            }
+
+            let Some(source_state) = self.outputs.get_mut(&source) else {
+                continue;
+            };
+            if !(source_state.consume_press() || source_state.on) {
+                continue;
+            }
+            let Some(color) = source_state.get_drop() else {
+                continue;
+            };
+            let source_interval = source_state.drop_interval();
+
+            if let Some(mixer_state) = self.outputs.get_mut(&mixer).and_then(|output_state| output_state.mixer_state_mut()) {
+                mixer_state.fill_timer.set_duration(source_interval);
+                mixer_state.fill_timer.reset();
+                mixer_state.push(color);
+                self.events.push(TapEvent::DropDispensed(DropDispensed {
+                    output: source,
+                    color,
+                }));
+            }
+        }
+
+        for output_state in self.outputs.values_mut() {
+            if let Some(mixer_state) = output_state.mixer_state_mut() {
+                if mixer_state.mixer_on {
+                    mixer_state.blend();
                 }
             }
         }
-        Option::None
     }
-}
 
-#[derive(Default, Debug)]
-pub enum TapSpeed {
-    Slow,
-    #[default]
-    Medium,
-    Fast
-}
+    /// the output currently plugged into the input
+    pub fn connected_output(&self, input: InputId) -> Option<OutputId> {
+        self.connections
+            .iter()
+            .find(|(_, input_option)| **input_option == Some(input))
+            .map(|(output, _)| *output)
+    }
 
-#[derive(Debug, PartialEq, Eq, Hash, Clone)]
-pub enum DrinkInput {
-    Tap1,
-    Tap2,
-    Tap3,
-    Mixer1,
-    Mixer2,
+    /// pours a drop from the output plugged into a tap if it's switched on or has a press queued,
+    /// returning it with how long until the next one
+    pub fn take_drop(&mut self, input: InputId) -> Option<(OutputId, Color, Duration)> {
+        let output = self.connected_output(input)?;
+        let output_state = self.outputs.get_mut(&output)?;
+        if !(output_state.consume_press() || output_state.on) {
+            return None;
+        }
+        let color = output_state.get_drop()?;
+        let interval = output_state.drop_interval();
+        self.events.push(TapEvent::DropDispensed(DropDispensed { output, color }));
+        Some((output, color, interval))
+    }
+
+    pub fn output_name(&self, output: OutputId) -> Option<&str> {
+        self.outputs.get(&output).map(|output_state| output_state.name.as_str())
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, Hash, Component, Clone)]
-pub enum DrinkOutput {
-    Color1,
-    Color2,
-    Color3,
-    Mixer1,
-    Mixer2,
+/// an input a hose can be plugged into, either a tap that pours into a cup or a mixer inlet
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Component)]
+pub struct InputId(usize);
+
+/// a liquid source a hose can be plugged into, either a color tap or a mixer outlet
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy, Component)]
+pub struct OutputId(usize);
+
+#[derive(Debug, PartialEq, Eq)]
+enum InputType {
+    Tap,
+    Mixer(OutputId),
 }
 
 #[derive(Debug)]
 pub struct OutputState {
     pending_presses: usize,
+    max_pending_presses: usize,
+    pour_mode: PourMode,
     press_available_on: Timer,
-    pub on: bool,
+    on: bool,
+    flow_rate: f32,
+    pub name: String,
     output_type: OutputType,
 }
 
 impl OutputState {
-    fn new_color(color: Color) -> OutputState {
+    fn new_color(tap_config: &TapConfig) -> OutputState {
         OutputState {
+            name: tap_config.name.clone(),
             on: false,
-            press_available_on: Timer::new(Duration::from_millis(250), bevy::time::TimerMode::Once),
+            press_available_on: press_cooldown(&tap_config.pour),
             pending_presses: 0,
-            output_type: OutputType::Color(ColorOutputState::new(color)),
+            max_pending_presses: tap_config.pour.burst_size,
+            pour_mode: tap_config.pour.mode,
+            flow_rate: tap_config.flow_rate.clamp(MIN_FLOW_RATE, MAX_FLOW_RATE),
+            output_type: OutputType::Color(ColorOutputState::new(
+                Color::linear_rgb(tap_config.color[0], tap_config.color[1], tap_config.color[2]),
+                tap_config.reservoir,
+                Duration::from_secs_f32(tap_config.refill_time),
+            )),
         }
     }
 
-    fn new_mixer() -> OutputState {
+    fn new_mixer(name: String, flow_rate: f32, pour: &PourConfig) -> OutputState {
         OutputState {
+            name,
             on: false,
-            press_available_on: Timer::new(Duration::from_millis(250), bevy::time::TimerMode::Once),
+            press_available_on: press_cooldown(pour),
             pending_presses: 0,
+            max_pending_presses: pour.burst_size,
+            pour_mode: pour.mode,
+            flow_rate: flow_rate.clamp(MIN_FLOW_RATE, MAX_FLOW_RATE),
             output_type: OutputType::Mixer(MixerOutputState::new()),
         }
     }
 
-    pub fn drop_pressed(&mut self) {
-        self.pending_presses += 1;
-        if self.pending_presses > 3 {
-            self.pending_presses = 3;
+    /// drops per second
+    pub fn flow_rate(&self) -> f32 {
+        self.flow_rate
+    }
+
+    pub fn drop_interval(&self) -> Duration {
+        Duration::from_secs_f32(1. / self.flow_rate)
+    }
+
+    fn queue_press(&mut self) {
+        if self.pending_presses < self.max_pending_presses {
+            self.pending_presses += 1;
         }
     }
 
@@ -188,6 +490,10 @@ impl OutputState {
         self.pending_presses > 0
     }
 
+    pub fn pour_mode(&self) -> PourMode {
+        self.pour_mode
+    }
+
     /// returns true if press was available or false if press is still waiting on timer
     pub fn consume_press(&mut self) -> bool {
         if self.press_available_on.finished() && self.pending_presses > 0 {
@@ -201,39 +507,35 @@ impl OutputState {
 
     pub fn tick(&mut self, time: &Time<Virtual>) {
         self.press_available_on.tick(time.delta());
-    }
-
-    pub fn set_lightness(&mut self, lightness: f32) {
-        match self.output_type {
-            OutputType::Color(ref mut output_state) => {
-                output_state.light = lightness;
-            }
-            _ => {}
+        if let OutputType::Color(ref mut color_state) = self.output_type {
+            color_state.tick_refill(time);
         }
     }
 
-    pub fn set_mixer(&mut self, switch_on: bool) {
+    fn mixer_state_mut(&mut self) -> Option<&mut MixerOutputState> {
         match self.output_type {
-            OutputType::Mixer(ref mut mixer_state) => {
-                mixer_state.mixer_on = switch_on;
-            }
-            _ => {}
+            OutputType::Mixer(ref mut mixer_state) => Some(mixer_state),
+            _ => None,
         }
     }
 
     pub fn get_drop(&mut self) -> Option<Color> {
         match &mut self.output_type {
-            OutputType::Color(color_output_state) => Option::Some(
-                color_output_state
-                    .start_color
-                    .clone()
-                    .mix(&Color::WHITE, color_output_state.light),
-            ),
+            OutputType::Color(color_output_state) => {
+                if !color_output_state.take_drop() {
+                    return Option::None;
+                }
+                Option::Some(color_output_state.color())
+            }
             OutputType::Mixer(mixer_output_state) => mixer_output_state.mixer.pop_front(),
         }
     }
 }
 
+fn press_cooldown(pour: &PourConfig) -> Timer {
+    Timer::new(Duration::from_secs_f32(pour.cooldown.max(0.)), bevy::time::TimerMode::Once)
+}
+
 #[derive(Debug)]
 enum OutputType {
     Color(ColorOutputState),
@@ -244,14 +546,70 @@ enum OutputType {
 pub struct ColorOutputState {
     pub start_color: Color,
     pub light: f32,
+    /// drops left in the reservoir
+    reservoir: usize,
+    capacity: usize,
+    /// running while the reservoir is being refilled, no drops pour until it finishes
+    refill: Timer,
+    refilling: bool,
 }
 
 impl ColorOutputState {
-    fn new(color: Color) -> ColorOutputState {
+    fn new(color: Color, capacity: usize, refill_time: Duration) -> ColorOutputState {
         ColorOutputState {
             start_color: color,
             light: 0.0,
+            reservoir: capacity,
+            capacity,
+            refill: Timer::new(refill_time, bevy::time::TimerMode::Once),
+            refilling: false,
+        }
+    }
+
+    /// start color diluted by the lightness dial
+    fn color(&self) -> Color {
+        dilute(self.start_color, self.light)
+    }
+
+    /// removes a drop from the reservoir, returns false if there is nothing to pour
+    fn take_drop(&mut self) -> bool {
+        if self.refilling || self.reservoir == 0 {
+            return false;
+        }
+        self.reservoir -= 1;
+        true
+    }
+
+    /// returns false if the reservoir was already full or refilling
+    fn start_refill(&mut self) -> bool {
+        if self.refilling || self.reservoir == self.capacity {
+            return false;
+        }
+        self.refill.reset();
+        self.refilling = true;
+        true
+    }
+
+    fn tick_refill(&mut self, time: &Time<Virtual>) {
+        if !self.refilling {
+            return;
+        }
+        self.refill.tick(time.delta());
+        if self.refill.finished() {
+            self.reservoir = self.capacity;
+            self.refilling = false;
+        }
+    }
+
+    fn level(&self) -> f32 {
+        if self.capacity == 0 {
+            return 0.;
         }
+        let level = self.reservoir as f32 / self.capacity as f32;
+        if self.refilling {
+            return level + (1. - level) * self.refill.fraction();
+        }
+        level
     }
 }
 
@@ -259,13 +617,34 @@ impl ColorOutputState {
 pub struct MixerOutputState {
     mixer: VecDeque<Color>,
     mixer_on: bool,
+    fill_timer: Timer,
 }
 
 impl MixerOutputState {
     fn new() -> MixerOutputState {
         MixerOutputState {
             mixer_on: false,
-            mixer: VecDeque::with_capacity(64),
+            mixer: VecDeque::with_capacity(MIXER_CAPACITY),
+            fill_timer: Timer::new(Duration::from_millis(200), bevy::time::TimerMode::Once),
         }
     }
+
+    fn is_full(&self) -> bool {
+        self.mixer.len() >= MIXER_CAPACITY
+    }
+
+    fn push(&mut self, color: Color) {
+        if !self.is_full() {
+            self.mixer.push_back(color);
+        }
+    }
+
+    /// mixes everything in the mixer into a single color, like stirring paint
+    fn blend(&mut self) {
+        let Some(blended) = mix_pigments(self.mixer.iter()) else {
+            return;
+        };
+
+        self.mixer.iter_mut().for_each(|color| *color = blended);
+    }
 }
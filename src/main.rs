@@ -1,4 +1,4 @@
-use assets::{AssetInitializerPlugin, BarAssets, GameUiAssets, OrderAssets};
+use assets::{toml_loader::check_config, AssetInitializerPlugin, BarAssets, GameUiAssets, OrderAssets};
 use bevy::{
     prelude::*, render::{
         settings::{Backends, RenderCreation, WgpuSettings},
@@ -9,15 +9,21 @@ use bevy_asset_loader::loading_state::{
     config::ConfigureLoadingState, LoadingState, LoadingStateAppExt,
 };
 use bevy_rapier2d::plugin::{NoUserData, RapierPhysicsPlugin};
+use error_screen::ErrorScreenPlugin;
 use game::{status_bar::StatusBarMaterial, GamePlugin, StatePlugin};
 
 pub mod assets;
+pub mod error_screen;
 pub mod game;
 
 pub const WINDOW_WIDTH: f32 = 800.;
 pub const WINDOW_HEIGHT: f32 = 400.;
 
 fn main() {
+    if std::env::args().any(|arg| arg == "--check-config") {
+        std::process::exit(if check_config() { 0 } else { 1 });
+    }
+
     App::new()
         .add_plugins((
             DefaultPlugins
@@ -41,6 +47,10 @@ fn main() {
                     }),
                     ..default()
                 })
+                .set(AssetPlugin {
+                    watch_for_changes_override: Some(cfg!(all(feature = "file_watcher", debug_assertions))),
+                    ..default()
+                })
                 .set(RenderPlugin {
                     render_creation: RenderCreation::Automatic(WgpuSettings {
                         backends: Some(Backends::VULKAN),
@@ -54,6 +64,7 @@ fn main() {
             // WorldInspectorPlugin::new(),
             // RapierDebugRenderPlugin::default(),
             AssetInitializerPlugin,
+            ErrorScreenPlugin,
             Material2dPlugin::<StatusBarMaterial>::default(),
             GamePlugin::run_on_state(GameStates::Playing),
         ))
@@ -61,6 +72,7 @@ fn main() {
         .add_loading_state(
             LoadingState::new(GameStates::AssetLoading)
                 .continue_to_state(GameStates::Playing)
+                .on_failure_continue_to_state(GameStates::LoadError)
                 .load_collection::<OrderAssets>()
                 .load_collection::<BarAssets>()
                 .load_collection::<GameUiAssets>(),
@@ -84,6 +96,7 @@ pub enum GameStates {
     StartMenu,
     Playing,
     EndScreen,
+    LoadError,
 }
 
 pub fn despawn_screen<T: Component>(to_despawn: Query<Entity, With<T>>, mut commands: Commands) {